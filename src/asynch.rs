@@ -0,0 +1,196 @@
+//! Bus access of the async drivers, built on the `embedded-hal-async` SPI
+//! device, delay and wait traits.
+
+use crate::{
+    command::Command,
+    error::{Error, PinError},
+    interface::{Async, Bus, SpiInterface},
+    size::DisplaySize,
+};
+
+use core::future::{poll_fn, Future};
+use core::pin::pin;
+use core::task::Poll;
+use embedded_hal::digital::v2::OutputPin;
+use embedded_hal_async::delay::DelayUs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::spi::{SpiBus, SpiDevice};
+
+pub type Ssd1680<SPI, OPIN, OPIN2, P, SIZE> =
+    crate::ssd1680::Ssd1680<Bus<SPI, OPIN, OPIN2, P, Async>, SIZE>;
+
+pub type Ssd1680TriColor<SPI, OPIN, OPIN2, P, SIZE> =
+    crate::ssd1680tricolor::Ssd1680TriColor<Bus<SPI, OPIN, OPIN2, P, Async>, SIZE>;
+
+impl<SPI, OPIN, OPIN2, E, DE, RE, BE, P> Bus<SPI, OPIN, OPIN2, P, Async>
+where
+    SPI: SpiDevice<Error = E>,
+    SPI::Bus: SpiBus,
+    OPIN: OutputPin<Error = DE>,
+    OPIN2: OutputPin<Error = RE>,
+    P: Wait<Error = BE>,
+{
+    pub async fn send_command(
+        &mut self,
+        command: Command,
+    ) -> Result<(), Error<E, PinError<DE, RE, BE>>> {
+        self.dc.set_low().map_err(Error::dc)?;
+        self.spi.write(&[command as u8]).await.map_err(Error::Comm)
+    }
+
+    pub async fn send_data(&mut self, buffer: &[u8]) -> Result<(), Error<E, PinError<DE, RE, BE>>> {
+        self.dc.set_high().map_err(Error::dc)?;
+        self.spi.write(buffer).await.map_err(Error::Comm)
+    }
+
+    /// Clock `buffer.len()` bytes in from the controller, reads share the
+    /// data line so the SPI has to support it
+    pub async fn read_data(
+        &mut self,
+        buffer: &mut [u8],
+    ) -> Result<(), Error<E, PinError<DE, RE, BE>>> {
+        self.dc.set_high().map_err(Error::dc)?;
+        self.spi.read(buffer).await.map_err(Error::Comm)
+    }
+
+    pub async fn hardware_reset<D>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error<E, PinError<DE, RE, BE>>>
+    where
+        D: DelayUs,
+    {
+        // without a reset pin the software reset in power_up has to do
+        if let Some(reset) = &mut self.reset {
+            reset.set_low().map_err(Error::reset)?;
+            delay.delay_ms(10).await.ok();
+            reset.set_high().map_err(Error::reset)?;
+        }
+        Ok(())
+    }
+
+    /// Wait for the busy pin to go low, giving up after `timeout_ms`.
+    /// Without a busy pin waits `busy_ms`, the worst case for the operation.
+    pub async fn busy_wait<D>(
+        &mut self,
+        delay: &mut D,
+        busy_ms: u32,
+        timeout_ms: u32,
+    ) -> Result<(), Error<E, PinError<DE, RE, BE>>>
+    where
+        D: DelayUs,
+    {
+        let busy = match &mut self.busy {
+            Some(busy) => busy,
+            None => {
+                delay.delay_ms(busy_ms).await.ok();
+                return Ok(());
+            }
+        };
+
+        let timeout = delay.delay_ms(timeout_ms);
+        match with_timeout(busy.wait_for_low(), timeout).await {
+            Some(result) => result.map_err(Error::busy),
+            None => Err(Error::BusyTimeout),
+        }
+    }
+}
+
+impl<SPI, OPIN, OPIN2, E, DE, RE, BE, P, SIZE> SpiInterface<Bus<SPI, OPIN, OPIN2, P, Async>, SIZE>
+where
+    SPI: SpiDevice<Error = E>,
+    SPI::Bus: SpiBus,
+    OPIN: OutputPin<Error = DE>,
+    OPIN2: OutputPin<Error = RE>,
+    P: Wait<Error = BE>,
+    SIZE: DisplaySize,
+{
+    crate::interface::sequences!(async; DelayUs; Error<E, PinError<DE, RE, BE>>);
+    crate::interface::sequences!(read async; DelayUs; Error<E, PinError<DE, RE, BE>>);
+}
+
+impl<SPI, OPIN, OPIN2, E, DE, RE, BE, P, SIZE> Ssd1680<SPI, OPIN, OPIN2, P, SIZE>
+where
+    SPI: SpiDevice<Error = E>,
+    SPI::Bus: SpiBus,
+    OPIN: OutputPin<Error = DE>,
+    OPIN2: OutputPin<Error = RE>,
+    P: Wait<Error = BE>,
+    SIZE: DisplaySize,
+{
+    crate::driver::sequences!(async; DelayUs; Error<E, PinError<DE, RE, BE>>);
+    crate::ssd1680::sequences!(async; DelayUs; Error<E, PinError<DE, RE, BE>>);
+    crate::driver::sequences!(read async; DelayUs; Error<E, PinError<DE, RE, BE>>);
+}
+
+impl<SPI, OPIN, OPIN2, E, DE, RE, BE, P, SIZE> Ssd1680TriColor<SPI, OPIN, OPIN2, P, SIZE>
+where
+    SPI: SpiDevice<Error = E>,
+    SPI::Bus: SpiBus,
+    OPIN: OutputPin<Error = DE>,
+    OPIN2: OutputPin<Error = RE>,
+    P: Wait<Error = BE>,
+    SIZE: DisplaySize,
+{
+    crate::driver::sequences!(async; DelayUs; Error<E, PinError<DE, RE, BE>>);
+    crate::ssd1680tricolor::sequences!(async; DelayUs; Error<E, PinError<DE, RE, BE>>);
+    crate::driver::sequences!(read async; DelayUs; Error<E, PinError<DE, RE, BE>>);
+}
+
+/// Output of `future`, or `None` when `timeout` completes first
+async fn with_timeout<F, T>(future: F, timeout: T) -> Option<F::Output>
+where
+    F: Future,
+    T: Future,
+{
+    let mut future = pin!(future);
+    let mut timeout = pin!(timeout);
+
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = future.as_mut().poll(cx) {
+            return Poll::Ready(Some(output));
+        }
+        if timeout.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(None);
+        }
+        Poll::Pending
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{display::DisplayRotation, pin::NoPin, size::DisplaySize250x122};
+
+    /// Only has to compile, the drivers take `None` for both optional pins
+    #[allow(dead_code)]
+    async fn without_reset_and_busy<SPI, OPIN, D>(spi: SPI, dc: OPIN, delay: &mut D)
+    where
+        SPI: SpiDevice,
+        SPI::Bus: SpiBus,
+        OPIN: OutputPin,
+        D: DelayUs,
+    {
+        let mut display = Ssd1680::new(
+            spi,
+            dc,
+            None::<NoPin>,
+            None::<NoPin>,
+            DisplaySize250x122,
+            DisplayRotation::Rotate0,
+        );
+        display.flush(delay).await.ok();
+        display.power_down().await.ok();
+
+        let mut display = Ssd1680TriColor::new(
+            display.interface.bus.spi,
+            display.interface.bus.dc,
+            None::<NoPin>,
+            None::<NoPin>,
+            DisplaySize250x122,
+            DisplayRotation::Rotate0,
+        );
+        display.flush(delay).await.ok();
+    }
+}
@@ -0,0 +1,169 @@
+//! Blocking drivers, built on the `embedded-hal` blocking SPI, delay and
+//! input pin traits for firmware without an async executor.
+//!
+//! The SPI is written to directly, chip select has to be held low by the
+//! application or tied low on the board.
+
+use crate::{
+    command::Command,
+    error::{Error, PinError},
+    interface::{Blocking, Bus, SpiInterface},
+    size::DisplaySize,
+};
+
+use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::blocking::spi::{Transfer, Write};
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+
+pub type Ssd1680<SPI, OPIN, OPIN2, P, SIZE> =
+    crate::ssd1680::Ssd1680<Bus<SPI, OPIN, OPIN2, P, Blocking>, SIZE>;
+
+pub type Ssd1680TriColor<SPI, OPIN, OPIN2, P, SIZE> =
+    crate::ssd1680tricolor::Ssd1680TriColor<Bus<SPI, OPIN, OPIN2, P, Blocking>, SIZE>;
+
+impl<SPI, OPIN, OPIN2, E, DE, RE, BE, P> Bus<SPI, OPIN, OPIN2, P, Blocking>
+where
+    SPI: Write<u8, Error = E>,
+    OPIN: OutputPin<Error = DE>,
+    OPIN2: OutputPin<Error = RE>,
+    P: InputPin<Error = BE>,
+{
+    pub fn send_command(&mut self, command: Command) -> Result<(), Error<E, PinError<DE, RE, BE>>> {
+        self.dc.set_low().map_err(Error::dc)?;
+        self.spi.write(&[command as u8]).map_err(Error::Comm)
+    }
+
+    pub fn send_data(&mut self, buffer: &[u8]) -> Result<(), Error<E, PinError<DE, RE, BE>>> {
+        self.dc.set_high().map_err(Error::dc)?;
+        self.spi.write(buffer).map_err(Error::Comm)
+    }
+
+    pub fn hardware_reset<D>(&mut self, delay: &mut D) -> Result<(), Error<E, PinError<DE, RE, BE>>>
+    where
+        D: DelayMs<u32>,
+    {
+        // without a reset pin the software reset in power_up has to do
+        if let Some(reset) = &mut self.reset {
+            reset.set_low().map_err(Error::reset)?;
+            delay.delay_ms(10);
+            reset.set_high().map_err(Error::reset)?;
+        }
+        Ok(())
+    }
+
+    /// Poll the busy pin until it goes low, giving up after `timeout_ms`.
+    /// Without a busy pin waits `busy_ms`, the worst case for the operation.
+    pub fn busy_wait<D>(
+        &mut self,
+        delay: &mut D,
+        busy_ms: u32,
+        timeout_ms: u32,
+    ) -> Result<(), Error<E, PinError<DE, RE, BE>>>
+    where
+        D: DelayMs<u32>,
+    {
+        let busy = match &mut self.busy {
+            Some(busy) => busy,
+            None => {
+                delay.delay_ms(busy_ms);
+                return Ok(());
+            }
+        };
+
+        let mut waited_ms = 0;
+        while busy.is_high().map_err(Error::busy)? {
+            if waited_ms >= timeout_ms {
+                return Err(Error::BusyTimeout);
+            }
+            delay.delay_ms(1);
+            waited_ms += 1;
+        }
+        Ok(())
+    }
+}
+
+impl<SPI, OPIN, OPIN2, E, DE, RE, BE, P> Bus<SPI, OPIN, OPIN2, P, Blocking>
+where
+    SPI: Write<u8, Error = E> + Transfer<u8, Error = E>,
+    OPIN: OutputPin<Error = DE>,
+    OPIN2: OutputPin<Error = RE>,
+    P: InputPin<Error = BE>,
+{
+    /// Clock `buffer.len()` bytes in from the controller, reads share the
+    /// data line so the SPI has to support it
+    pub fn read_data(&mut self, buffer: &mut [u8]) -> Result<(), Error<E, PinError<DE, RE, BE>>> {
+        self.dc.set_high().map_err(Error::dc)?;
+        self.spi.transfer(buffer).map_err(Error::Comm)?;
+        Ok(())
+    }
+}
+
+impl<SPI, OPIN, OPIN2, E, DE, RE, BE, P, SIZE>
+    SpiInterface<Bus<SPI, OPIN, OPIN2, P, Blocking>, SIZE>
+where
+    SPI: Write<u8, Error = E>,
+    OPIN: OutputPin<Error = DE>,
+    OPIN2: OutputPin<Error = RE>,
+    P: InputPin<Error = BE>,
+    SIZE: DisplaySize,
+{
+    crate::interface::sequences!(blocking; DelayMs<u32>; Error<E, PinError<DE, RE, BE>>);
+}
+
+impl<SPI, OPIN, OPIN2, E, DE, RE, BE, P, SIZE>
+    SpiInterface<Bus<SPI, OPIN, OPIN2, P, Blocking>, SIZE>
+where
+    SPI: Write<u8, Error = E> + Transfer<u8, Error = E>,
+    OPIN: OutputPin<Error = DE>,
+    OPIN2: OutputPin<Error = RE>,
+    P: InputPin<Error = BE>,
+    SIZE: DisplaySize,
+{
+    crate::interface::sequences!(read blocking; DelayMs<u32>; Error<E, PinError<DE, RE, BE>>);
+}
+
+impl<SPI, OPIN, OPIN2, E, DE, RE, BE, P, SIZE> Ssd1680<SPI, OPIN, OPIN2, P, SIZE>
+where
+    SPI: Write<u8, Error = E>,
+    OPIN: OutputPin<Error = DE>,
+    OPIN2: OutputPin<Error = RE>,
+    P: InputPin<Error = BE>,
+    SIZE: DisplaySize,
+{
+    crate::driver::sequences!(blocking; DelayMs<u32>; Error<E, PinError<DE, RE, BE>>);
+    crate::ssd1680::sequences!(blocking; DelayMs<u32>; Error<E, PinError<DE, RE, BE>>);
+}
+
+impl<SPI, OPIN, OPIN2, E, DE, RE, BE, P, SIZE> Ssd1680<SPI, OPIN, OPIN2, P, SIZE>
+where
+    SPI: Write<u8, Error = E> + Transfer<u8, Error = E>,
+    OPIN: OutputPin<Error = DE>,
+    OPIN2: OutputPin<Error = RE>,
+    P: InputPin<Error = BE>,
+    SIZE: DisplaySize,
+{
+    crate::driver::sequences!(read blocking; DelayMs<u32>; Error<E, PinError<DE, RE, BE>>);
+}
+
+impl<SPI, OPIN, OPIN2, E, DE, RE, BE, P, SIZE> Ssd1680TriColor<SPI, OPIN, OPIN2, P, SIZE>
+where
+    SPI: Write<u8, Error = E>,
+    OPIN: OutputPin<Error = DE>,
+    OPIN2: OutputPin<Error = RE>,
+    P: InputPin<Error = BE>,
+    SIZE: DisplaySize,
+{
+    crate::driver::sequences!(blocking; DelayMs<u32>; Error<E, PinError<DE, RE, BE>>);
+    crate::ssd1680tricolor::sequences!(blocking; DelayMs<u32>; Error<E, PinError<DE, RE, BE>>);
+}
+
+impl<SPI, OPIN, OPIN2, E, DE, RE, BE, P, SIZE> Ssd1680TriColor<SPI, OPIN, OPIN2, P, SIZE>
+where
+    SPI: Write<u8, Error = E> + Transfer<u8, Error = E>,
+    OPIN: OutputPin<Error = DE>,
+    OPIN2: OutputPin<Error = RE>,
+    P: InputPin<Error = BE>,
+    SIZE: DisplaySize,
+{
+    crate::driver::sequences!(read blocking; DelayMs<u32>; Error<E, PinError<DE, RE, BE>>);
+}
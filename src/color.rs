@@ -0,0 +1,162 @@
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::pixelcolor::{
+    raw::{RawData, RawU2},
+    BinaryColor, PixelColor, Rgb565, Rgb888, RgbColor,
+};
+
+/// Only for the Black/White/Color-Displays
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TriColor {
+    /// Black color
+    Black,
+    /// White color
+    White,
+    /// Chromatic color
+    Chromatic,
+}
+
+impl TriColor {
+    /// Get the color encoding of the color for one bit
+    pub fn get_bit_value(self) -> u8 {
+        match self {
+            TriColor::White => 1u8,
+            TriColor::Black | TriColor::Chromatic => 0u8,
+        }
+    }
+
+    /// Gets a full byte of black or white pixels
+    pub fn get_byte_value(self) -> u8 {
+        match self {
+            TriColor::White => 0xff,
+            TriColor::Black | TriColor::Chromatic => 0x00,
+        }
+    }
+}
+
+/// The low bit is the RAM1 (white) bit and the high bit the RAM2
+/// (chromatic) bit, like the two planes of the frame buffer
+#[cfg(feature = "graphics")]
+impl PixelColor for TriColor {
+    type Raw = RawU2;
+}
+
+#[cfg(feature = "graphics")]
+impl From<RawU2> for TriColor {
+    /// `0b10` is chromatic too, RAM2 takes precedence on the panel
+    fn from(raw: RawU2) -> Self {
+        match raw.into_inner() {
+            0b00 => TriColor::Black,
+            0b01 => TriColor::White,
+            _ => TriColor::Chromatic,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<TriColor> for RawU2 {
+    fn from(color: TriColor) -> Self {
+        RawU2::new(match color {
+            TriColor::Black => 0b00,
+            TriColor::White => 0b01,
+            TriColor::Chromatic => 0b11,
+        })
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<BinaryColor> for TriColor {
+    /// `On` is black, like on the monochrome driver
+    fn from(color: BinaryColor) -> Self {
+        match color {
+            BinaryColor::On => TriColor::Black,
+            BinaryColor::Off => TriColor::White,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<TriColor> for BinaryColor {
+    /// Chromatic pixels are dark on a monochrome panel, they become `On`
+    fn from(color: TriColor) -> Self {
+        match color {
+            TriColor::White => BinaryColor::Off,
+            TriColor::Black | TriColor::Chromatic => BinaryColor::On,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<Rgb888> for TriColor {
+    /// Saturated colors are chromatic, the rest is black or white by luma
+    fn from(color: Rgb888) -> Self {
+        let (r, g, b) = (color.r() as u32, color.g() as u32, color.b() as u32);
+        let chroma = r.max(g).max(b) - r.min(g).min(b);
+        let luma = (r * 299 + g * 587 + b * 114) / 1000;
+
+        if chroma >= 0x80 {
+            TriColor::Chromatic
+        } else if luma >= 0x80 {
+            TriColor::White
+        } else {
+            TriColor::Black
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<TriColor> for Rgb888 {
+    /// Chromatic pixels are red, the more common of the red and yellow panels
+    fn from(color: TriColor) -> Self {
+        match color {
+            TriColor::Black => Rgb888::BLACK,
+            TriColor::White => Rgb888::WHITE,
+            TriColor::Chromatic => Rgb888::RED,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<Rgb565> for TriColor {
+    fn from(color: Rgb565) -> Self {
+        Rgb888::from(color).into()
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<TriColor> for Rgb565 {
+    fn from(color: TriColor) -> Self {
+        Rgb888::from(color).into()
+    }
+}
+
+#[cfg(all(test, feature = "graphics"))]
+mod tests {
+    use super::*;
+
+    const COLORS: [TriColor; 3] = [TriColor::Black, TriColor::White, TriColor::Chromatic];
+
+    #[test]
+    fn raw_round_trips() {
+        for color in COLORS {
+            assert_eq!(TriColor::from(RawU2::from(color)), color);
+        }
+        assert_eq!(TriColor::from(RawU2::new(0b10)), TriColor::Chromatic);
+    }
+
+    #[test]
+    fn rgb_round_trips() {
+        for color in COLORS {
+            assert_eq!(TriColor::from(Rgb888::from(color)), color);
+            assert_eq!(TriColor::from(Rgb565::from(color)), color);
+        }
+        assert_eq!(TriColor::from(Rgb888::YELLOW), TriColor::Chromatic);
+        assert_eq!(
+            TriColor::from(Rgb888::new(0x40, 0x40, 0x40)),
+            TriColor::Black
+        );
+        assert_eq!(
+            TriColor::from(Rgb888::new(0xC0, 0xC0, 0xC0)),
+            TriColor::White
+        );
+    }
+}
@@ -0,0 +1,206 @@
+/// SSD1680 Commands
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// SW Reset
+    Reset = 0x12,
+    DataMode = 0x11,
+    Border = 0x3C,
+    GateVoltage = 0x03,
+    SourceVoltage = 0x04,
+    RamXCount = 0x4E,
+    RamYCount = 0x4F,
+    RamXPos = 0x44,
+    RamYPos = 0x45,
+    Control = 0x01,
+    Vcom = 0x2C,
+    DispCtrl1 = 0x21,
+    DispCtrl2 = 0x22,
+    MasterActivate = 0x20,
+    WriteRAM1 = 0x24,
+    WriteRAM2 = 0x26,
+    Sleep = 0x10,
+    WriteLut = 0x32,
+    LutEndOption = 0x3F,
+    TempSensor = 0x18,
+    TempWrite = 0x1A,
+    TempRead = 0x1B,
+}
+
+impl TryFrom<u8> for Command {
+    /// The byte isn't a command the driver sends
+    type Error = u8;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        Ok(match byte {
+            0x12 => Command::Reset,
+            0x11 => Command::DataMode,
+            0x3C => Command::Border,
+            0x03 => Command::GateVoltage,
+            0x04 => Command::SourceVoltage,
+            0x4E => Command::RamXCount,
+            0x4F => Command::RamYCount,
+            0x44 => Command::RamXPos,
+            0x45 => Command::RamYPos,
+            0x01 => Command::Control,
+            0x2C => Command::Vcom,
+            0x21 => Command::DispCtrl1,
+            0x22 => Command::DispCtrl2,
+            0x20 => Command::MasterActivate,
+            0x24 => Command::WriteRAM1,
+            0x26 => Command::WriteRAM2,
+            0x10 => Command::Sleep,
+            0x32 => Command::WriteLut,
+            0x3F => Command::LutEndOption,
+            0x18 => Command::TempSensor,
+            0x1A => Command::TempWrite,
+            0x1B => Command::TempRead,
+            byte => return Err(byte),
+        })
+    }
+}
+
+/// Display Update Control 2 sequences
+#[derive(Clone, Copy, Debug)]
+pub enum UpdateMode {
+    /// Load temperature and waveform from OTP, full update (display mode 1)
+    Full = 0xF4,
+    /// Load temperature and waveform from OTP, partial update (display
+    /// mode 2), only pixels differing between RAM1 and RAM2 are driven
+    Partial = 0xFC,
+}
+
+impl UpdateMode {
+    /// Control byte for the sequence, skipping the temperature and
+    /// waveform loads when a custom waveform is already in the LUT
+    /// register, and only the temperature load when the host wrote the
+    /// temperature register
+    pub fn control(self, custom_waveform: bool, host_temperature: bool) -> u8 {
+        if custom_waveform {
+            self as u8 & !0x30
+        } else if host_temperature {
+            self as u8 & !0x20
+        } else {
+            self as u8
+        }
+    }
+}
+
+/// Data Entry Mode, the X address is always incremented first so a
+/// framebuffer row is written to the sources of one gate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataEntry {
+    /// Y increment, framebuffer row `r` is written to gate `r`
+    YIncrement = 0x03,
+    /// Y decrement, framebuffer row `r` is written to gate `WIDTH - 1 - r`
+    YDecrement = 0x01,
+}
+
+impl DataEntry {
+    /// Gate framebuffer row `row` is written to on a panel of `width` gates
+    pub fn gate(self, row: u16, width: u16) -> u16 {
+        match self {
+            DataEntry::YIncrement => row,
+            DataEntry::YDecrement => width - 1 - row,
+        }
+    }
+}
+
+/// Temperature sensor the controller loads the temperature register from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TemperatureSensor {
+    /// Sensor built into the controller
+    Internal = 0x80,
+    /// I2C sensor wired to the controller's TSDA and TSCL pins
+    External = 0x48,
+}
+
+/// Power state of the controller as far as the driver knows, operations
+/// needing RAM or an update wake it up first
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerState {
+    /// Powered on or reset, registers not initialised yet
+    Reset,
+    /// Initialised and accepting commands
+    Awake,
+    /// Deep sleep, ignoring SPI until the next hardware reset
+    DeepSleep,
+}
+
+/// Clock on, load the temperature and display mode 1 waveform, clock off
+pub const LOAD_TEMPERATURE: u8 = 0xB1;
+
+/// Commands sent after a reset, before the RAM window is set up
+pub const INIT_SEQUENCE: [(Command, &[u8]); 4] = [
+    (Command::Border, &[0x05]),
+    (Command::Vcom, &[0x36]),
+    (Command::GateVoltage, &[0x17]),
+    (Command::SourceVoltage, &[0x41, 0x00, 0x32]),
+];
+
+/// Longest wait for the busy pin to be released, a tricolor full update takes
+/// around 15 seconds
+pub const DEFAULT_BUSY_TIMEOUT_MS: u32 = 30_000;
+
+/// Worst case busy time of a software reset, waited out when there is no busy
+/// pin
+pub const RESET_BUSY_MS: u32 = 10;
+/// RAM writes don't keep the controller busy
+pub const WRITE_BUSY_MS: u32 = 0;
+/// Worst case busy time of a temperature load
+pub const TEMPERATURE_BUSY_MS: u32 = 100;
+/// Worst case busy time of a partial update
+pub const PARTIAL_UPDATE_BUSY_MS: u32 = 1_000;
+/// Worst case busy time of a monochrome full update
+pub const MONO_UPDATE_BUSY_MS: u32 = 4_000;
+/// Worst case busy time of a tricolor full update
+pub const TRICOLOR_UPDATE_BUSY_MS: u32 = 20_000;
+
+/// Gate addresses are 9 bits, the low byte followed by the MSB
+pub fn gate_address(y: u16) -> [u8; 2] {
+    [y as u8, (y >> 8) as u8 & 0x01]
+}
+
+/// Temperature register value in degrees Celsius, a 12 bit two's complement
+/// value in 1/16 degrees, MSB first
+pub fn temperature(bytes: [u8; 2]) -> f32 {
+    let raw = ((bytes[0] as i16) << 8 | bytes[1] as i16) >> 4;
+    raw as f32 / 16.0
+}
+
+/// Temperature register value for `celsius`, the inverse of [`temperature`]
+pub fn temperature_register(celsius: f32) -> [u8; 2] {
+    let raw = ((celsius * 16.0) as i16).clamp(-2048, 2047);
+    [(raw >> 4) as u8, (raw << 4) as u8]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temperature_decodes_twelve_bit_register() {
+        assert_eq!(temperature([0x19, 0x00]), 25.0);
+        assert_eq!(temperature([0x19, 0x80]), 25.5);
+        assert_eq!(temperature([0x00, 0x10]), 0.0625);
+        assert_eq!(temperature([0xE6, 0x80]), -25.5);
+        assert_eq!(temperature([0xFF, 0xF0]), -0.0625);
+        assert_eq!(temperature([0x7F, 0xF0]), 127.9375);
+        assert_eq!(temperature([0x80, 0x00]), -128.0);
+    }
+
+    #[test]
+    fn temperature_register_clamps_to_range() {
+        assert_eq!(temperature_register(25.0), [0x19, 0x00]);
+        assert_eq!(temperature_register(-25.5), [0xE6, 0x80]);
+        assert_eq!(temperature_register(200.0), [0x7F, 0xF0]);
+        assert_eq!(temperature_register(-300.0), [0x80, 0x00]);
+    }
+
+    #[test]
+    fn temperature_register_round_trips() {
+        for raw in -2048..=2047 {
+            let celsius = raw as f32 / 16.0;
+            assert_eq!(temperature(temperature_register(celsius)), celsius);
+        }
+    }
+}
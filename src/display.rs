@@ -1,5 +1,7 @@
+use crate::command::DataEntry;
+
 /// Display rotation
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DisplayRotation {
     /// No rotation, normal display
     Rotate0,
@@ -11,32 +13,69 @@ pub enum DisplayRotation {
     Rotate270,
 }
 
-pub fn find_rotation(
-    x: u32,
-    y: u32,
-    height: u32,
-    width: u32,
-    rotation: DisplayRotation,
-) -> (u32, u32) {
-    let nx;
-    let ny;
-    match rotation {
-        DisplayRotation::Rotate0 => {
-            nx = x;
-            ny = y;
-        }
-        DisplayRotation::Rotate90 => {
-            nx = y;
-            ny = width - 1 - x;
+impl DisplayRotation {
+    /// Logical width and height of a `width` gates by `height` sources panel,
+    /// swapped in portrait
+    pub(crate) fn dimensions(self, width: u32, height: u32) -> (u32, u32) {
+        match self {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (width, height),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (height, width),
         }
-        DisplayRotation::Rotate180 => {
-            nx = height - 1 - x;
-            ny = width - 1 - y;
+    }
+
+    /// Data entry mode laying the framebuffer rows out on the gates, the
+    /// unrotated display has its first column on the last gate
+    pub(crate) fn data_entry(self) -> DataEntry {
+        match self {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate90 => DataEntry::YDecrement,
+            DisplayRotation::Rotate180 | DisplayRotation::Rotate270 => DataEntry::YIncrement,
         }
-        DisplayRotation::Rotate270 => {
-            nx = width - 1 - y;
-            ny = x;
+    }
+
+    /// Framebuffer row and source of the logical pixel `(x, y)`, `None` when
+    /// it is off the `width` gates by `height` sources panel.
+    ///
+    /// The gate direction is handled by [`data_entry`](Self::data_entry),
+    /// only the sources are mirrored here as the controller can't reverse
+    /// the bits of a RAM byte.
+    pub(crate) fn ram_position(
+        self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) -> Option<(u32, u32)> {
+        let (logical_width, logical_height) = self.dimensions(width, height);
+        let x = u32::try_from(x).ok().filter(|&x| x < logical_width)?;
+        let y = u32::try_from(y).ok().filter(|&y| y < logical_height)?;
+
+        Some(match self {
+            DisplayRotation::Rotate0 => (x, y),
+            DisplayRotation::Rotate90 => (y, height - 1 - x),
+            DisplayRotation::Rotate180 => (x, height - 1 - y),
+            DisplayRotation::Rotate270 => (y, x),
+        })
+    }
+
+    /// Gate and source showing the logical pixel `(x, y)` on a `width` gates
+    /// by `height` sources panel, `None` when it is off the panel.
+    ///
+    /// Turns the panel clockwise back to `Rotate0`, which has its first column
+    /// on the last gate, rather than following the RAM layout, so it can tell
+    /// whether a frame buffer puts the pixel where it belongs.
+    pub fn panel_position(self, x: i32, y: i32, width: u16, height: u16) -> Option<(u16, u16)> {
+        let (logical_width, logical_height) = self.dimensions(width.into(), height.into());
+        if !(0..logical_width as i32).contains(&x) || !(0..logical_height as i32).contains(&y) {
+            return None;
         }
+
+        let (width, height) = (i32::from(width), i32::from(height));
+        let (x, y) = match self {
+            DisplayRotation::Rotate0 => (x, y),
+            DisplayRotation::Rotate90 => (y, height - 1 - x),
+            DisplayRotation::Rotate180 => (width - 1 - x, height - 1 - y),
+            DisplayRotation::Rotate270 => (width - 1 - y, x),
+        };
+        Some(((width - 1 - x) as u16, y as u16))
     }
-    (nx, ny)
 }
@@ -0,0 +1,265 @@
+//! Dithering draw targets, drawing grayscale and RGB images onto the black,
+//! white and chromatic pixels of the panel.
+//!
+//! ```ignore
+//! let mut dither = Dither::new(&mut display, Dithering::FloydSteinberg);
+//! Image::new(&photo, Point::zero()).draw(&mut dither)?;
+//! ```
+
+use crate::color::TriColor;
+use core::marker::PhantomData;
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::Dimensions,
+    pixelcolor::{BinaryColor, PixelColor, Rgb888, RgbColor},
+    prelude::*,
+    primitives::Rectangle,
+};
+
+/// Widest area Floyd–Steinberg diffuses errors over, the long side of the
+/// largest panel. Columns past it are quantized without diffusion.
+const MAX_WIDTH: usize = 296;
+
+/// 4x4 Bayer threshold map
+const BAYER: [[i16; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Dithering algorithm
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dithering {
+    /// Ordered 4x4 Bayer dithering, works for pixels drawn in any order
+    Ordered,
+    /// Floyd–Steinberg error diffusion, for areas filled row by row like
+    /// images and `fill_contiguous`. Single pixels are dithered ordered.
+    FloydSteinberg,
+}
+
+/// Panel colors dithering picks from
+pub trait DitherColor: PixelColor {
+    /// Panel color closest to `rgb` and the RGB value it shows as, `chromatic`
+    /// is the color of the panel's third pigment
+    fn nearest(rgb: [i16; 3], chromatic: Rgb888) -> (Self, [i16; 3]);
+}
+
+impl DitherColor for BinaryColor {
+    /// Black or white by luma, `On` is black like on the monochrome driver
+    fn nearest([r, g, b]: [i16; 3], _chromatic: Rgb888) -> (Self, [i16; 3]) {
+        let luma = (r as i32 * 299 + g as i32 * 587 + b as i32 * 114) / 1000;
+
+        if luma >= 0x80 {
+            (BinaryColor::Off, [0xFF; 3])
+        } else {
+            (BinaryColor::On, [0x00; 3])
+        }
+    }
+}
+
+impl DitherColor for TriColor {
+    /// Black, white or chromatic, whichever is closest in RGB
+    fn nearest(rgb: [i16; 3], chromatic: Rgb888) -> (Self, [i16; 3]) {
+        let chromatic = [chromatic.r(), chromatic.g(), chromatic.b()].map(i16::from);
+
+        [
+            (TriColor::Black, [0x00; 3]),
+            (TriColor::White, [0xFF; 3]),
+            (TriColor::Chromatic, chromatic),
+        ]
+        .into_iter()
+        .min_by_key(|(_, value)| {
+            (0..3)
+                .map(|i| (rgb[i] as i32 - value[i] as i32).pow(2))
+                .sum::<i32>()
+        })
+        .unwrap()
+    }
+}
+
+/// Draw target taking `C` colors, like `Gray8`, `Rgb565` or `Rgb888`, and
+/// dithering them onto a [`BinaryColor`] or [`TriColor`] target
+pub struct Dither<'a, D, C> {
+    target: &'a mut D,
+    dithering: Dithering,
+    chromatic: Rgb888,
+    color: PhantomData<C>,
+}
+
+impl<'a, D, C> Dither<'a, D, C>
+where
+    D: DrawTarget,
+    D::Color: DitherColor,
+{
+    /// Chromatic pixels are taken to be red, see
+    /// [`with_chromatic`](Self::with_chromatic) for yellow panels
+    pub fn new(target: &'a mut D, dithering: Dithering) -> Self {
+        Self {
+            target,
+            dithering,
+            chromatic: Rgb888::RED,
+            color: PhantomData,
+        }
+    }
+
+    /// Color the chromatic pixels show as, picked for colors close to it
+    pub fn with_chromatic(mut self, chromatic: Rgb888) -> Self {
+        self.chromatic = chromatic;
+        self
+    }
+}
+
+impl<D, C> Dimensions for Dither<'_, D, C>
+where
+    D: DrawTarget,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<D, C> DrawTarget for Dither<'_, D, C>
+where
+    D: DrawTarget,
+    D::Color: DitherColor,
+    C: PixelColor + Into<Rgb888>,
+{
+    type Color = C;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let chromatic = self.chromatic;
+
+        self.target
+            .draw_iter(pixels.into_iter().map(|Pixel(point, color)| {
+                let threshold =
+                    BAYER[point.y.rem_euclid(4) as usize][point.x.rem_euclid(4) as usize];
+                // spread the thresholds evenly around 0
+                let offset = (threshold * 2 + 1) * 0xFF / 32 - 0x7F;
+                let rgb = channels(color.into()).map(|channel| channel + offset);
+
+                Pixel(point, D::Color::nearest(rgb, chromatic).0)
+            }))
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let pixels = area
+            .points()
+            .zip(colors)
+            .map(|(point, color)| Pixel(point, color));
+
+        if self.dithering == Dithering::Ordered {
+            return self.draw_iter(pixels);
+        }
+
+        let width = area.size.width as usize;
+        let chromatic = self.chromatic;
+
+        // errors for this row and the next, shifted a column right so the
+        // pixel below left is in range
+        let mut current = [[0i16; 3]; MAX_WIDTH + 2];
+        let mut next = [[0i16; 3]; MAX_WIDTH + 2];
+
+        self.target
+            .draw_iter(pixels.enumerate().map(|(i, Pixel(point, color))| {
+                let column = i % width;
+                if column == 0 && i > 0 {
+                    current = next;
+                    next = [[0; 3]; MAX_WIDTH + 2];
+                }
+
+                let mut rgb = channels(color.into());
+                if column >= MAX_WIDTH {
+                    return Pixel(point, D::Color::nearest(rgb, chromatic).0);
+                }
+
+                for (channel, error) in rgb.iter_mut().zip(current[column + 1]) {
+                    *channel += error;
+                }
+                let (color, value) = D::Color::nearest(rgb, chromatic);
+
+                for c in 0..3 {
+                    let error = rgb[c] - value[c];
+                    current[column + 2][c] += error * 7 / 16;
+                    next[column][c] += error * 3 / 16;
+                    next[column + 1][c] += error * 5 / 16;
+                    next[column + 2][c] += error / 16;
+                }
+
+                Pixel(point, color)
+            }))
+    }
+}
+
+fn channels(color: Rgb888) -> [i16; 3] {
+    [color.r(), color.g(), color.b()].map(i16::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        display::DisplayRotation,
+        framebuffer::{Framebuffer, TriColorFramebuffer},
+        size::DisplaySize250x122,
+    };
+    use embedded_graphics_core::pixelcolor::Gray8;
+
+    fn count<F>(area: Rectangle, pixel: F) -> usize
+    where
+        F: Fn(Point) -> bool,
+    {
+        area.points().filter(|&point| pixel(point)).count()
+    }
+
+    #[test]
+    fn gray_dithers_to_half_black() {
+        let area = Rectangle::new(Point::zero(), Size::new(64, 64));
+
+        for dithering in [Dithering::Ordered, Dithering::FloydSteinberg] {
+            let mut framebuffer = Framebuffer::<DisplaySize250x122>::new(DisplayRotation::Rotate0);
+            Dither::new(&mut framebuffer, dithering)
+                .fill_solid(&area, Gray8::new(0x80))
+                .unwrap();
+
+            let black = count(area, |point| {
+                framebuffer.pixel(point.x, point.y) == Some(BinaryColor::On)
+            });
+            assert!((1900..=2200).contains(&black), "{:?} {}", dithering, black);
+        }
+    }
+
+    #[test]
+    fn tricolor_picks_the_closest_pigment() {
+        let area = Rectangle::new(Point::zero(), Size::new(16, 16));
+
+        for dithering in [Dithering::Ordered, Dithering::FloydSteinberg] {
+            let mut framebuffer =
+                TriColorFramebuffer::<DisplaySize250x122>::new(DisplayRotation::Rotate0);
+            let mut dither =
+                Dither::new(&mut framebuffer, dithering).with_chromatic(Rgb888::YELLOW);
+
+            dither.fill_solid(&area, Rgb888::YELLOW).unwrap();
+            let chromatic = count(area, |point| {
+                framebuffer.pixel(point.x, point.y) == Some(TriColor::Chromatic)
+            });
+            assert_eq!(chromatic, 16 * 16, "{:?}", dithering);
+
+            // dark yellow mixes yellow and black, never white
+            Dither::new(&mut framebuffer, dithering)
+                .with_chromatic(Rgb888::YELLOW)
+                .fill_solid(&area, Rgb888::new(0x80, 0x80, 0x00))
+                .unwrap();
+            let chromatic = count(area, |point| {
+                framebuffer.pixel(point.x, point.y) == Some(TriColor::Chromatic)
+            });
+            let white = count(area, |point| {
+                framebuffer.pixel(point.x, point.y) == Some(TriColor::White)
+            });
+            assert!(chromatic > 0 && chromatic < 16 * 16, "{:?}", dithering);
+            assert_eq!(white, 0, "{:?}", dithering);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! What the monochrome and tricolor drivers have in common, they only differ
+//! in the frame buffer and how it is flushed.
+
+/// Constructor, settings, pixel access and `DrawTarget` of a driver struct
+/// holding a `framebuffer` and an `interface`, written as
+/// `driver!(Ssd1680, Framebuffer, BinaryColor, MONO_UPDATE_BUSY_MS)`
+macro_rules! driver {
+    ($driver:ident, $framebuffer:ident, $color:ty, $update_busy_ms:expr) => {
+        impl<SPI, DC, RST, BUSY, MODE, SIZE>
+            $driver<$crate::interface::Bus<SPI, DC, RST, BUSY, MODE>, SIZE>
+        where
+            SIZE: $crate::size::DisplaySize,
+        {
+            /// Without a reset pin the controller is only software reset and never
+            /// put in deep sleep, without a busy pin each operation waits its worst
+            /// case time.
+            pub fn new(
+                spi: SPI,
+                dc: DC,
+                reset: Option<RST>,
+                busy: Option<BUSY>,
+                _size: SIZE,
+                display_rotation: $crate::display::DisplayRotation,
+            ) -> Self {
+                Self {
+                    interface: $crate::interface::SpiInterface::new(
+                        $crate::interface::Bus::new(spi, dc, reset, busy),
+                        display_rotation.data_entry(),
+                        $update_busy_ms,
+                    ),
+                    framebuffer: $framebuffer::new(display_rotation),
+                }
+            }
+        }
+
+        impl<BUS, SIZE> $driver<BUS, SIZE>
+        where
+            SIZE: $crate::size::DisplaySize,
+        {
+            /// Whether an update was started and not waited for yet
+            pub fn is_updating(&self) -> bool {
+                self.interface.is_updating()
+            }
+
+            /// Whether the controller is reset, awake or in deep sleep. Writes and
+            /// updates wake it up with a hardware reset and the power up sequence
+            /// when it is not awake.
+            pub fn power_state(&self) -> $crate::command::PowerState {
+                self.interface.power_state()
+            }
+
+            pub fn framebuffer(&self) -> &$framebuffer<SIZE> {
+                &self.framebuffer
+            }
+
+            /// Swap in a frame buffer drawn elsewhere, its rotation is programmed
+            /// with the next write. Returns the previous frame buffer.
+            pub fn replace_framebuffer(
+                &mut self,
+                framebuffer: $framebuffer<SIZE>,
+            ) -> $framebuffer<SIZE> {
+                self.interface
+                    .set_data_entry(framebuffer.rotation().data_entry());
+                core::mem::replace(&mut self.framebuffer, framebuffer)
+            }
+
+            pub fn rotation(&self) -> $crate::display::DisplayRotation {
+                self.framebuffer.rotation()
+            }
+
+            /// Change the orientation, the data entry mode follows with the next
+            /// write. Pixels already drawn keep their place in the buffer, redraw the
+            /// screen after switching.
+            pub fn set_rotation(&mut self, display_rotation: $crate::display::DisplayRotation) {
+                self.framebuffer.set_rotation(display_rotation);
+                self.interface.set_data_entry(display_rotation.data_entry());
+            }
+
+            /// Longest time to wait for the controller to release the busy pin
+            /// before giving up with [`Error::BusyTimeout`](crate::Error::BusyTimeout),
+            /// defaults to [`DEFAULT_BUSY_TIMEOUT_MS`](crate::DEFAULT_BUSY_TIMEOUT_MS)
+            pub fn set_busy_timeout(&mut self, timeout_ms: u32) {
+                self.interface.set_busy_timeout(timeout_ms);
+            }
+
+            /// Sensor the controller loads the temperature from to pick the OTP
+            /// waveform, selected on every power up. Defaults to
+            /// [`TemperatureSensor::Internal`](crate::TemperatureSensor::Internal).
+            pub fn set_temperature_sensor(&mut self, sensor: $crate::command::TemperatureSensor) {
+                self.interface.set_temperature_sensor(sensor);
+            }
+
+            /// Write `celsius` to the temperature register before every update,
+            /// selecting the OTP waveform from the host's reading instead of the
+            /// sensor. `None` goes back to loading it from the sensor.
+            pub fn set_temperature(&mut self, celsius: Option<f32>) {
+                self.interface.set_temperature(celsius);
+            }
+
+            /// Use `waveform` instead of the one stored in OTP for the following
+            /// updates, it is uploaded every time the controller is powered up and
+            /// with the next write when it is awake. `None` goes back to the OTP
+            /// waveform.
+            pub fn set_waveform(&mut self, waveform: Option<$crate::waveform::Waveform>) {
+                self.interface.set_waveform(waveform);
+            }
+
+            /// Set the logical pixel `(x, y)`, pixels off the panel are clipped
+            pub fn set_pixel(&mut self, x: i32, y: i32, color: $color) {
+                self.framebuffer.set_pixel(x, y, color);
+            }
+
+            /// Set the logical pixel `(x, y)`, failing when it is off the panel
+            pub fn try_set_pixel(
+                &mut self,
+                x: i32,
+                y: i32,
+                color: $color,
+            ) -> Result<(), $crate::error::OutOfBounds> {
+                self.framebuffer.try_set_pixel(x, y, color)
+            }
+        }
+
+        #[cfg(feature = "graphics")]
+        impl<BUS, SIZE> embedded_graphics_core::draw_target::DrawTarget for $driver<BUS, SIZE>
+        where
+            SIZE: $crate::size::DisplaySize,
+        {
+            type Color = $color;
+            type Error = core::convert::Infallible;
+
+            fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+            where
+                I: IntoIterator<Item = embedded_graphics_core::Pixel<Self::Color>>,
+            {
+                embedded_graphics_core::draw_target::DrawTarget::draw_iter(
+                    &mut self.framebuffer,
+                    pixels,
+                )
+            }
+        }
+
+        #[cfg(feature = "graphics")]
+        impl<BUS, SIZE> embedded_graphics_core::geometry::OriginDimensions for $driver<BUS, SIZE>
+        where
+            SIZE: $crate::size::DisplaySize,
+        {
+            fn size(&self) -> embedded_graphics_core::geometry::Size {
+                embedded_graphics_core::geometry::OriginDimensions::size(&self.framebuffer)
+            }
+        }
+    };
+}
+
+pub(crate) use driver;
+
+/// Update, power and temperature methods of both drivers, expanded for each
+/// bus like [`sequences`](crate::interface::sequences). The flush methods
+/// come from the driver's own `sequences`.
+macro_rules! sequences {
+    (async; $delay:path; $error:ty) => {
+        $crate::driver::sequences!(@write [async] [.await] $delay; $error);
+    };
+    (blocking; $delay:path; $error:ty) => {
+        $crate::driver::sequences!(@write [] [] $delay; $error);
+    };
+    (read async; $delay:path; $error:ty) => {
+        $crate::driver::sequences!(@read [async] [.await] $delay; $error);
+    };
+    (read blocking; $delay:path; $error:ty) => {
+        $crate::driver::sequences!(@read [] [] $delay; $error);
+    };
+
+    (@write [$($async:tt)*] [$($await:tt)*] $delay:path; $error:ty) => {
+        /// Show the RAM contents with a full update, waiting out the refresh
+        pub $($async)* fn flush_update<D>(&mut self, delay: &mut D) -> Result<(), $error>
+        where
+            D: $delay,
+        {
+            self.interface
+                .update($crate::command::UpdateMode::Full, delay)
+                $($await)*
+        }
+
+        /// Start a full update and return without waiting for the refresh, so
+        /// the application can carry on meanwhile. Finish it with
+        /// [`wait_update_complete`](Self::wait_update_complete), the other
+        /// operations wait for it on their own.
+        pub $($async)* fn begin_update<D>(&mut self, delay: &mut D) -> Result<(), $error>
+        where
+            D: $delay,
+        {
+            self.interface
+                .begin_update($crate::command::UpdateMode::Full, delay)
+                $($await)*
+        }
+
+        /// Wait for the update started with [`begin_update`](Self::begin_update),
+        /// returns at once when there is none. Without a busy pin this waits the
+        /// worst case refresh time.
+        pub $($async)* fn wait_update_complete<D>(&mut self, delay: &mut D) -> Result<(), $error>
+        where
+            D: $delay,
+        {
+            self.interface.wait_update_complete(delay)$($await)*
+        }
+
+        pub $($async)* fn power_down(&mut self) -> Result<(), $error> {
+            self.interface.power_down()$($await)*
+        }
+
+        pub $($async)* fn power_up<D>(&mut self, delay: &mut D) -> Result<(), $error>
+        where
+            D: $delay,
+        {
+            self.interface.power_up(delay)$($await)*
+        }
+    };
+
+    (@read [$($async:tt)*] [$($await:tt)*] $delay:path; $error:ty) => {
+        /// Read the panel's internal temperature sensor, in degrees Celsius.
+        /// The controller is woken up for the read and put back to sleep unless
+        /// it was awake, the SPI has to be able to read back on the shared data
+        /// line.
+        pub $($async)* fn read_temperature<D>(&mut self, delay: &mut D) -> Result<f32, $error>
+        where
+            D: $delay,
+        {
+            let power = self.interface.power_state();
+            self.interface.wake(delay)$($await)*?;
+
+            let temperature = self.interface.read_temperature(delay)$($await)*?;
+
+            if power != $crate::command::PowerState::Awake {
+                self.interface.power_down()$($await)*?;
+            }
+            Ok(temperature)
+        }
+    };
+}
+
+pub(crate) use sequences;
@@ -0,0 +1,663 @@
+//! Frame buffers laid out the way they are written to the controller's RAM,
+//! independent of the SPI and pins so screens can be drawn off-device, in
+//! tests or on a server, and handed to a driver with `replace_framebuffer`
+//! before flushing.
+//!
+//! Each gate line is one row of [`DisplaySize::STRIDE`] bytes, sources packed
+//! 8 per byte MSB first. Which gate a row lands on is up to the data entry
+//! mode the drivers program for the [`DisplayRotation`].
+
+use crate::{color::TriColor, display::DisplayRotation, error::OutOfBounds, size::DisplaySize};
+
+#[cfg(feature = "graphics")]
+use core::ops::RangeInclusive;
+
+#[cfg(feature = "std")]
+use std::{io, vec};
+
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{
+    draw_target::DrawTarget, geometry::OriginDimensions, geometry::Size, pixelcolor::BinaryColor,
+    prelude::*, primitives::Rectangle,
+};
+
+/// Monochrome frame buffer, written to RAM1 and RAM2
+pub struct Framebuffer<SIZE>
+where
+    SIZE: DisplaySize,
+{
+    buffer: SIZE::Buffer,
+    display_rotation: DisplayRotation,
+}
+
+impl<SIZE> Framebuffer<SIZE>
+where
+    SIZE: DisplaySize,
+{
+    /// White frame buffer
+    pub fn new(display_rotation: DisplayRotation) -> Self {
+        Self {
+            buffer: SIZE::buffer(0xFF), // inverted
+            display_rotation,
+        }
+    }
+
+    /// Raw bytes, a cleared bit is a black pixel
+    pub fn buffer(&self) -> &[u8] {
+        self.buffer.as_ref()
+    }
+
+    pub fn rotation(&self) -> DisplayRotation {
+        self.display_rotation
+    }
+
+    /// Pixels already drawn keep their place in the buffer, redraw after
+    /// changing the rotation
+    pub fn set_rotation(&mut self, display_rotation: DisplayRotation) {
+        self.display_rotation = display_rotation;
+    }
+
+    /// Logical size for the current rotation
+    #[cfg(feature = "graphics")]
+    pub fn size(&self) -> Size {
+        let (width, height) = self
+            .display_rotation
+            .dimensions(SIZE::WIDTH.into(), SIZE::HEIGHT.into());
+        Size::new(width, height)
+    }
+
+    /// Set the logical pixel `(x, y)`, pixels off the panel are clipped
+    pub fn set_pixel(&mut self, x: i32, y: i32, color: BinaryColor) {
+        self.try_set_pixel(x, y, color).ok();
+    }
+
+    /// Set the logical pixel `(x, y)`, failing when it is off the panel
+    pub fn try_set_pixel(&mut self, x: i32, y: i32, color: BinaryColor) -> Result<(), OutOfBounds> {
+        let (index, bit) =
+            position::<SIZE>(x, y, self.display_rotation).ok_or(OutOfBounds { x, y })?;
+
+        let buffer = self.buffer.as_mut();
+        match color {
+            BinaryColor::On => {
+                buffer[index] &= !bit;
+            }
+            BinaryColor::Off => {
+                buffer[index] |= bit;
+            }
+        }
+        Ok(())
+    }
+
+    /// Logical pixel `(x, y)`, `None` when it is off the panel
+    pub fn pixel(&self, x: i32, y: i32) -> Option<BinaryColor> {
+        let (index, bit) = position::<SIZE>(x, y, self.display_rotation)?;
+
+        Some(if self.buffer.as_ref()[index] & bit == 0 {
+            BinaryColor::On
+        } else {
+            BinaryColor::Off
+        })
+    }
+
+    /// Write the frame as a binary PBM image in logical orientation, for
+    /// golden images and bug reports
+    #[cfg(feature = "std")]
+    pub fn write_pbm<W>(&self, mut writer: W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        let (width, height) = self
+            .display_rotation
+            .dimensions(SIZE::WIDTH.into(), SIZE::HEIGHT.into());
+        write!(writer, "P4\n{} {}\n", width, height)?;
+
+        // 1 is black in PBM too, rows are padded to whole bytes
+        let mut row = vec![0; (width as usize).div_ceil(8)];
+        for y in 0..height as i32 {
+            row.fill(0);
+            for x in 0..width as i32 {
+                if self.pixel(x, y) == Some(BinaryColor::On) {
+                    row[x as usize / 8] |= 0x80 >> (x % 8);
+                }
+            }
+            writer.write_all(&row)?;
+        }
+        Ok(())
+    }
+
+    /// Columns, in bytes, and rows of the buffer covering `area`, `None` when
+    /// it is outside of the display
+    #[cfg(feature = "graphics")]
+    pub(crate) fn window(
+        &self,
+        area: Rectangle,
+    ) -> Option<(RangeInclusive<usize>, RangeInclusive<usize>)> {
+        let display = Rectangle::new(Point::zero(), self.size());
+        let area = area.intersection(&display);
+        let bottom_right = area.bottom_right()?;
+
+        let rotation = self.display_rotation;
+        let (row0, source0) = rotation.ram_position(
+            area.top_left.x,
+            area.top_left.y,
+            SIZE::WIDTH.into(),
+            SIZE::HEIGHT.into(),
+        )?;
+        let (row1, source1) = rotation.ram_position(
+            bottom_right.x,
+            bottom_right.y,
+            SIZE::WIDTH.into(),
+            SIZE::HEIGHT.into(),
+        )?;
+
+        let columns = (source0.min(source1) / 8) as usize..=(source0.max(source1) / 8) as usize;
+        let rows = row0.min(row1) as usize..=row0.max(row1) as usize;
+
+        Some((columns, rows))
+    }
+}
+
+/// Black/white and chromatic frame buffers, with the same layout as
+/// [`Framebuffer`], written to RAM1 and RAM2
+pub struct TriColorFramebuffer<SIZE>
+where
+    SIZE: DisplaySize,
+{
+    buffer: SIZE::Buffer,
+    buffer2: SIZE::Buffer,
+    display_rotation: DisplayRotation,
+}
+
+impl<SIZE> TriColorFramebuffer<SIZE>
+where
+    SIZE: DisplaySize,
+{
+    /// White frame buffer
+    pub fn new(display_rotation: DisplayRotation) -> Self {
+        Self {
+            buffer: SIZE::buffer(0xFF), // inverted
+            buffer2: SIZE::buffer(0x00),
+            display_rotation,
+        }
+    }
+
+    /// Black/white plane, written to RAM1, a cleared bit is a black pixel
+    pub fn buffer(&self) -> &[u8] {
+        self.buffer.as_ref()
+    }
+
+    /// Chromatic plane, written to RAM2, a set bit is a chromatic pixel
+    pub fn buffer2(&self) -> &[u8] {
+        self.buffer2.as_ref()
+    }
+
+    pub fn rotation(&self) -> DisplayRotation {
+        self.display_rotation
+    }
+
+    /// Pixels already drawn keep their place in the buffer, redraw after
+    /// changing the rotation
+    pub fn set_rotation(&mut self, display_rotation: DisplayRotation) {
+        self.display_rotation = display_rotation;
+    }
+
+    /// Logical size for the current rotation
+    #[cfg(feature = "graphics")]
+    pub fn size(&self) -> Size {
+        let (width, height) = self
+            .display_rotation
+            .dimensions(SIZE::WIDTH.into(), SIZE::HEIGHT.into());
+        Size::new(width, height)
+    }
+
+    /// Logical pixel `(x, y)`, `None` when it is off the panel
+    pub fn pixel(&self, x: i32, y: i32) -> Option<TriColor> {
+        let (index, bit) = position::<SIZE>(x, y, self.display_rotation)?;
+
+        Some(if self.buffer2.as_ref()[index] & bit != 0 {
+            TriColor::Chromatic
+        } else if self.buffer.as_ref()[index] & bit != 0 {
+            TriColor::White
+        } else {
+            TriColor::Black
+        })
+    }
+
+    /// Write both planes composited as a binary PPM image in logical
+    /// orientation, chromatic pixels in red
+    #[cfg(feature = "std")]
+    pub fn write_ppm<W>(&self, mut writer: W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        let (width, height) = self
+            .display_rotation
+            .dimensions(SIZE::WIDTH.into(), SIZE::HEIGHT.into());
+        write!(writer, "P6\n{} {}\n255\n", width, height)?;
+
+        let mut row = vec![0; width as usize * 3];
+        for y in 0..height as i32 {
+            for (x, rgb) in row.chunks_exact_mut(3).enumerate() {
+                rgb.copy_from_slice(match self.pixel(x as i32, y) {
+                    Some(TriColor::Black) => &[0x00, 0x00, 0x00],
+                    Some(TriColor::Chromatic) => &[0xFF, 0x00, 0x00],
+                    _ => &[0xFF, 0xFF, 0xFF],
+                });
+            }
+            writer.write_all(&row)?;
+        }
+        Ok(())
+    }
+
+    /// Set the logical pixel `(x, y)`, pixels off the panel are clipped
+    pub fn set_pixel(&mut self, x: i32, y: i32, color: TriColor) {
+        self.try_set_pixel(x, y, color).ok();
+    }
+
+    /// Set the logical pixel `(x, y)`, failing when it is off the panel
+    pub fn try_set_pixel(&mut self, x: i32, y: i32, color: TriColor) -> Result<(), OutOfBounds> {
+        let (index, bit) =
+            position::<SIZE>(x, y, self.display_rotation).ok_or(OutOfBounds { x, y })?;
+
+        let (buffer, buffer2) = (self.buffer.as_mut(), self.buffer2.as_mut());
+
+        match color {
+            TriColor::Black => {
+                buffer[index] &= !bit;
+                buffer2[index] &= !bit;
+            }
+            TriColor::Chromatic => {
+                buffer[index] |= bit;
+                buffer2[index] |= bit;
+            }
+            TriColor::White => {
+                buffer[index] |= bit;
+                buffer2[index] &= !bit;
+            }
+        }
+        Ok(())
+    }
+
+    /// Black plane alone, `On` draws black and `Off` white, leaving the
+    /// chromatic plane as it is. Chromatic pixels still show over black ones.
+    pub fn black_layer(&mut self) -> Layer<'_, SIZE> {
+        Layer {
+            framebuffer: self,
+            plane: Plane::Black,
+        }
+    }
+
+    /// Chromatic plane alone, `On` draws chromatic and `Off` lets the black
+    /// plane show through, for highlights over a monochrome layout
+    pub fn chromatic_layer(&mut self) -> Layer<'_, SIZE> {
+        Layer {
+            framebuffer: self,
+            plane: Plane::Chromatic,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Plane {
+    Black,
+    Chromatic,
+}
+
+/// One plane of a [`TriColorFramebuffer`] as a monochrome draw target, from
+/// [`TriColorFramebuffer::black_layer`] and
+/// [`TriColorFramebuffer::chromatic_layer`]
+pub struct Layer<'a, SIZE>
+where
+    SIZE: DisplaySize,
+{
+    framebuffer: &'a mut TriColorFramebuffer<SIZE>,
+    plane: Plane,
+}
+
+impl<SIZE> Layer<'_, SIZE>
+where
+    SIZE: DisplaySize,
+{
+    /// Logical size for the current rotation
+    #[cfg(feature = "graphics")]
+    pub fn size(&self) -> Size {
+        self.framebuffer.size()
+    }
+
+    /// Set the logical pixel `(x, y)` of this plane, pixels off the panel are
+    /// clipped
+    pub fn set_pixel(&mut self, x: i32, y: i32, color: BinaryColor) {
+        self.try_set_pixel(x, y, color).ok();
+    }
+
+    /// Set the logical pixel `(x, y)` of this plane, failing when it is off
+    /// the panel
+    pub fn try_set_pixel(&mut self, x: i32, y: i32, color: BinaryColor) -> Result<(), OutOfBounds> {
+        let (index, bit) = position::<SIZE>(x, y, self.framebuffer.display_rotation)
+            .ok_or(OutOfBounds { x, y })?;
+
+        // RAM1 is inverted, a cleared bit is black
+        let (byte, ink) = match self.plane {
+            Plane::Black => (&mut self.framebuffer.buffer.as_mut()[index], color.is_off()),
+            Plane::Chromatic => (&mut self.framebuffer.buffer2.as_mut()[index], color.is_on()),
+        };
+
+        if ink {
+            *byte |= bit;
+        } else {
+            *byte &= !bit;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<SIZE> DrawTarget for Framebuffer<SIZE>
+where
+    SIZE: DisplaySize,
+{
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        pixels
+            .into_iter()
+            .for_each(|Pixel(pos, color)| self.set_pixel(pos.x, pos.y, color));
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<SIZE> OriginDimensions for Framebuffer<SIZE>
+where
+    SIZE: DisplaySize,
+{
+    fn size(&self) -> Size {
+        Framebuffer::size(self)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<SIZE> DrawTarget for TriColorFramebuffer<SIZE>
+where
+    SIZE: DisplaySize,
+{
+    type Color = TriColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        pixels
+            .into_iter()
+            .for_each(|Pixel(pos, color)| self.set_pixel(pos.x, pos.y, color));
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<SIZE> OriginDimensions for TriColorFramebuffer<SIZE>
+where
+    SIZE: DisplaySize,
+{
+    fn size(&self) -> Size {
+        TriColorFramebuffer::size(self)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<SIZE> DrawTarget for Layer<'_, SIZE>
+where
+    SIZE: DisplaySize,
+{
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        pixels
+            .into_iter()
+            .for_each(|Pixel(pos, color)| self.set_pixel(pos.x, pos.y, color));
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<SIZE> OriginDimensions for Layer<'_, SIZE>
+where
+    SIZE: DisplaySize,
+{
+    fn size(&self) -> Size {
+        Layer::size(self)
+    }
+}
+
+/// Byte index and bit mask of the logical pixel `(x, y)`
+fn position<SIZE>(x: i32, y: i32, rotation: DisplayRotation) -> Option<(usize, u8)>
+where
+    SIZE: DisplaySize,
+{
+    let (row, source) = rotation.ram_position(x, y, SIZE::WIDTH.into(), SIZE::HEIGHT.into())?;
+
+    Some((
+        source as usize / 8 + SIZE::STRIDE * row as usize,
+        0x80 >> (source % 8),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::size::TestSize;
+
+    const WIDTH: i32 = TestSize::WIDTH as i32;
+    const HEIGHT: i32 = TestSize::HEIGHT as i32;
+
+    const ROTATIONS: [DisplayRotation; 4] = [
+        DisplayRotation::Rotate0,
+        DisplayRotation::Rotate90,
+        DisplayRotation::Rotate180,
+        DisplayRotation::Rotate270,
+    ];
+
+    /// Gate and source of the logical pixel on the panel
+    fn expected(rotation: DisplayRotation, x: i32, y: i32) -> (u16, u16) {
+        rotation
+            .panel_position(x, y, TestSize::WIDTH, TestSize::HEIGHT)
+            .expect("pixel on the panel")
+    }
+
+    /// Gate and source of the only bit of `buffer` differing from `fill`,
+    /// once the rows are written in the rotation's data entry mode
+    fn physical(buffer: &[u8], fill: u8, rotation: DisplayRotation) -> (u16, u16) {
+        let mut changed = buffer.iter().enumerate().filter(|(_, &byte)| byte != fill);
+        let (index, byte) = changed.next().expect("no pixel set");
+        assert!(changed.next().is_none(), "more than one byte set");
+
+        let bits = byte ^ fill;
+        assert_eq!(bits.count_ones(), 1, "more than one pixel set");
+
+        let row = (index / TestSize::STRIDE) as u16;
+        let source = (index % TestSize::STRIDE) as u16 * 8 + bits.leading_zeros() as u16;
+        let gate = rotation.data_entry().gate(row, TestSize::WIDTH);
+        (gate, source)
+    }
+
+    fn dimensions(rotation: DisplayRotation) -> (i32, i32) {
+        let (width, height) = rotation.dimensions(WIDTH as u32, HEIGHT as u32);
+        (width as i32, height as i32)
+    }
+
+    fn logical_pixels(rotation: DisplayRotation) -> impl Iterator<Item = (i32, i32)> {
+        let (width, height) = dimensions(rotation);
+        (0..height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    #[test]
+    fn mono_rotations_map_to_panel() {
+        for rotation in ROTATIONS {
+            for (x, y) in logical_pixels(rotation) {
+                let mut framebuffer = Framebuffer::<TestSize>::new(rotation);
+                framebuffer.set_pixel(x, y, BinaryColor::On);
+
+                assert_eq!(
+                    physical(framebuffer.buffer(), 0xFF, rotation),
+                    expected(rotation, x, y),
+                    "{:?} ({}, {})",
+                    rotation,
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn tricolor_rotations_map_to_panel() {
+        for rotation in ROTATIONS {
+            for (x, y) in logical_pixels(rotation) {
+                let mut framebuffer = TriColorFramebuffer::<TestSize>::new(rotation);
+                framebuffer.set_pixel(x, y, TriColor::Black);
+
+                assert_eq!(
+                    physical(framebuffer.buffer(), 0xFF, rotation),
+                    expected(rotation, x, y),
+                    "{:?} ({}, {})",
+                    rotation,
+                    x,
+                    y
+                );
+
+                let mut framebuffer = TriColorFramebuffer::<TestSize>::new(rotation);
+                framebuffer.set_pixel(x, y, TriColor::Chromatic);
+
+                assert_eq!(
+                    physical(framebuffer.buffer2(), 0x00, rotation),
+                    expected(rotation, x, y),
+                    "{:?} ({}, {})",
+                    rotation,
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pixels_off_panel_are_clipped() {
+        for rotation in ROTATIONS {
+            let (width, height) = dimensions(rotation);
+            let off_panel = [
+                (width, 0),
+                (0, height),
+                (-1, 0),
+                (0, -1),
+                (i32::MIN, i32::MAX),
+            ];
+
+            let mut framebuffer = Framebuffer::<TestSize>::new(rotation);
+            for (x, y) in off_panel {
+                assert_eq!(
+                    rotation.panel_position(x, y, TestSize::WIDTH, TestSize::HEIGHT),
+                    None
+                );
+                framebuffer.set_pixel(x, y, BinaryColor::On);
+                assert_eq!(
+                    framebuffer.try_set_pixel(x, y, BinaryColor::On),
+                    Err(OutOfBounds { x, y })
+                );
+            }
+            assert!(framebuffer.buffer().iter().all(|&byte| byte == 0xFF));
+
+            let mut framebuffer = TriColorFramebuffer::<TestSize>::new(rotation);
+            for (x, y) in off_panel {
+                framebuffer.set_pixel(x, y, TriColor::Chromatic);
+                assert_eq!(
+                    framebuffer.try_set_pixel(x, y, TriColor::Chromatic),
+                    Err(OutOfBounds { x, y })
+                );
+            }
+            assert!(framebuffer.buffer2().iter().all(|&byte| byte == 0x00));
+        }
+    }
+
+    #[test]
+    fn layers_draw_one_plane_each() {
+        let mut framebuffer = TriColorFramebuffer::<TestSize>::new(DisplayRotation::Rotate0);
+        framebuffer.black_layer().set_pixel(0, 0, BinaryColor::On);
+        framebuffer.black_layer().set_pixel(1, 0, BinaryColor::On);
+        framebuffer
+            .chromatic_layer()
+            .set_pixel(1, 0, BinaryColor::On);
+        framebuffer
+            .chromatic_layer()
+            .set_pixel(2, 0, BinaryColor::On);
+
+        assert_eq!(framebuffer.pixel(0, 0), Some(TriColor::Black));
+        assert_eq!(framebuffer.pixel(1, 0), Some(TriColor::Chromatic));
+        assert_eq!(framebuffer.pixel(2, 0), Some(TriColor::Chromatic));
+        assert_eq!(framebuffer.pixel(3, 0), Some(TriColor::White));
+
+        // clearing the highlight uncovers the black pixel below
+        framebuffer
+            .chromatic_layer()
+            .set_pixel(1, 0, BinaryColor::Off);
+        framebuffer.black_layer().set_pixel(2, 0, BinaryColor::Off);
+        assert_eq!(framebuffer.pixel(1, 0), Some(TriColor::Black));
+        assert_eq!(framebuffer.pixel(2, 0), Some(TriColor::Chromatic));
+
+        assert_eq!(
+            framebuffer
+                .black_layer()
+                .try_set_pixel(WIDTH, 0, BinaryColor::On),
+            Err(OutOfBounds { x: WIDTH, y: 0 })
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn pbm_is_in_logical_orientation() {
+        let mut framebuffer = Framebuffer::<TestSize>::new(DisplayRotation::Rotate90);
+        framebuffer.set_pixel(0, 0, BinaryColor::On);
+        framebuffer.set_pixel(9, 19, BinaryColor::On);
+
+        let mut pbm = std::vec::Vec::new();
+        framebuffer.write_pbm(&mut pbm).unwrap();
+
+        // 13 by 20 in portrait, two bytes a row
+        let (header, rows) = pbm.split_at(b"P4\n13 20\n".len());
+        assert_eq!(header, b"P4\n13 20\n");
+        assert_eq!(rows.len(), 2 * 20);
+        assert_eq!(rows[0], 0x80);
+        assert_eq!(rows[19 * 2 + 1], 0x40);
+        assert_eq!(rows.iter().map(|byte| byte.count_ones()).sum::<u32>(), 2);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn ppm_composites_both_planes() {
+        let mut framebuffer = TriColorFramebuffer::<TestSize>::new(DisplayRotation::Rotate0);
+        framebuffer.set_pixel(0, 0, TriColor::Black);
+        framebuffer.set_pixel(1, 0, TriColor::Chromatic);
+
+        let mut ppm = std::vec::Vec::new();
+        framebuffer.write_ppm(&mut ppm).unwrap();
+
+        let (header, pixels) = ppm.split_at(b"P6\n20 13\n255\n".len());
+        assert_eq!(header, b"P6\n20 13\n255\n");
+        assert_eq!(pixels.len(), 20 * 13 * 3);
+        assert_eq!(
+            pixels[..9],
+            [0x00, 0x00, 0x00, 0xFF, 0x00, 0x00, 0xFF, 0xFF, 0xFF]
+        );
+    }
+}
@@ -1,130 +1,455 @@
-use crate::{command::Command, error::Error, DISPLAY_HEIGHT, DISPLAY_WIDTH};
-use core::convert::Infallible;
-use embedded_hal::digital::v2::OutputPin;
-use embedded_hal_async::delay::DelayUs;
-use embedded_hal_async::digital::Wait;
-use embedded_hal_async::spi::{SpiBus, SpiDevice};
-
-pub struct SpiInterface<SPI, OPIN, OPIN2, P>
-where
-    SPI: SpiDevice,
-    SPI::Bus: SpiBus,
-    OPIN: OutputPin<Error = Infallible>,
-    OPIN2: OutputPin<Error = Infallible>,
-    P: Wait<Error = Infallible>,
-{
-    spi: SPI,
-    dc: OPIN,
-    busy: P,
-    reset: OPIN2,
+//! Controller state and command sequences shared by the async and blocking
+//! drivers. Only the bus access differs between the two: each flavour
+//! implements `send_command`, `send_data`, `read_data`, `hardware_reset`
+//! and `busy_wait` on its [`Bus`] and expands [`sequences`] in an impl block
+//! of `SpiInterface<Bus<..>, SIZE>`, with or without `async`.
+
+use crate::{
+    command::{DataEntry, PowerState, TemperatureSensor, DEFAULT_BUSY_TIMEOUT_MS},
+    waveform::Waveform,
+};
+use core::marker::PhantomData;
+
+/// Bus used through the `embedded-hal-async` traits
+#[cfg(feature = "async")]
+pub enum Async {}
+
+/// Bus used through the `embedded-hal` blocking traits
+pub enum Blocking {}
+
+/// SPI and pins wired to the controller, `MODE` is [`Async`] or
+/// [`Blocking`]
+pub struct Bus<SPI, DC, RST, BUSY, MODE> {
+    pub(crate) spi: SPI,
+    pub(crate) dc: DC,
+    pub(crate) reset: Option<RST>,
+    pub(crate) busy: Option<BUSY>,
+    mode: PhantomData<MODE>,
 }
 
-impl<SPI, OPIN, OPIN2, E, P> SpiInterface<SPI, OPIN, OPIN2, P>
-where
-    SPI: SpiDevice<Error = E>,
-    SPI::Bus: SpiBus,
-    OPIN: OutputPin<Error = Infallible>,
-    OPIN2: OutputPin<Error = Infallible>,
-    P: Wait<Error = Infallible>,
-{
-    pub fn new(spi: SPI, dc: OPIN, reset: OPIN2, busy: P) -> Self {
+impl<SPI, DC, RST, BUSY, MODE> Bus<SPI, DC, RST, BUSY, MODE> {
+    pub fn new(spi: SPI, dc: DC, reset: Option<RST>, busy: Option<BUSY>) -> Self {
         Self {
             spi,
             dc,
-            busy,
             reset,
+            busy,
+            mode: PhantomData,
         }
     }
+}
 
-    pub async fn software_reset(&mut self) -> Result<(), Error<E>> {
-        self.send_command(Command::Reset).await?;
-        self.busy_wait().await
+pub struct SpiInterface<BUS, SIZE> {
+    pub(crate) bus: BUS,
+    pub(crate) data_entry: DataEntry,
+    /// `data_entry` changed while the controller was awake, it still runs
+    /// the previous one
+    pub(crate) data_entry_changed: bool,
+    pub(crate) waveform: Option<Waveform>,
+    /// `waveform` changed while the controller was awake, its LUT register
+    /// doesn't hold it yet
+    pub(crate) waveform_changed: bool,
+    pub(crate) temperature_sensor: TemperatureSensor,
+    pub(crate) temperature: Option<f32>,
+    pub(crate) busy_timeout_ms: u32,
+    pub(crate) update_busy_ms: u32,
+    pub(crate) updating: Option<u32>,
+    pub(crate) power: PowerState,
+    size: PhantomData<SIZE>,
+}
+
+impl<BUS, SIZE> SpiInterface<BUS, SIZE> {
+    /// `update_busy_ms` is the worst case busy time of a full update, waited
+    /// out when there is no busy pin. `data_entry` decides which gate each
+    /// framebuffer row is written to.
+    pub fn new(bus: BUS, data_entry: DataEntry, update_busy_ms: u32) -> Self {
+        Self {
+            bus,
+            data_entry,
+            data_entry_changed: false,
+            waveform: None,
+            waveform_changed: false,
+            temperature_sensor: TemperatureSensor::Internal,
+            temperature: None,
+            busy_timeout_ms: DEFAULT_BUSY_TIMEOUT_MS,
+            update_busy_ms,
+            updating: None,
+            power: PowerState::Reset,
+            size: PhantomData,
+        }
     }
 
-    pub async fn write_ram_frame_buffer(
-        &mut self,
-        buffer: &[u8],
-        command: Command,
-    ) -> Result<(), Error<E>> {
-        self.send_command(command).await?;
-        self.dc.set_high().ok();
-        self.spi.write(&buffer[..]).await.map_err(Error::Comm)
+    pub fn set_busy_timeout(&mut self, timeout_ms: u32) {
+        self.busy_timeout_ms = timeout_ms;
     }
 
-    pub async fn set_ram_address(&mut self, x: u8, y: u8) -> Result<(), Error<E>> {
-        self.send_command(Command::RamXCount).await?;
-        self.send_data(&[x]).await?;
+    /// Waveform uploaded on every power up, or on the next write when the
+    /// controller is awake. `None` uses the OTP one.
+    pub fn set_waveform(&mut self, waveform: Option<Waveform>) {
+        if self.power == PowerState::Awake {
+            self.waveform_changed = true;
+        }
+        self.waveform = waveform;
+    }
 
-        self.send_command(Command::RamYCount).await?;
-        self.send_data(&[y, ((y + 7) / 8)]).await
+    /// Data entry mode set on the next power up, or on the next write when
+    /// the controller is awake
+    pub fn set_data_entry(&mut self, data_entry: DataEntry) {
+        if data_entry != self.data_entry && self.power == PowerState::Awake {
+            self.data_entry_changed = true;
+        }
+        self.data_entry = data_entry;
     }
 
-    pub async fn send_command(&mut self, command: Command) -> Result<(), Error<E>> {
-        self.dc.set_low().ok();
-        self.spi.write(&[command as u8]).await.map_err(Error::Comm)
+    /// Sensor selected on every power up
+    pub fn set_temperature_sensor(&mut self, sensor: TemperatureSensor) {
+        self.temperature_sensor = sensor;
     }
 
-    pub async fn send_data(&mut self, buffer: &[u8]) -> Result<(), Error<E>> {
-        self.dc.set_high().ok();
-        self.spi.write(buffer).await.map_err(Error::Comm)
+    /// Temperature written before every update instead of loading it from
+    /// the sensor, `None` goes back to the sensor
+    pub fn set_temperature(&mut self, celsius: Option<f32>) {
+        self.temperature = celsius;
     }
 
-    pub async fn hardware_reset<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
-    where
-        D: DelayUs,
-    {
-        self.reset.set_low().ok();
-        delay.delay_ms(10).await.ok();
-        self.reset.set_high().ok();
-        Ok(())
+    /// Whether an update was started and not waited for yet
+    pub fn is_updating(&self) -> bool {
+        self.updating.is_some()
     }
 
-    pub async fn busy_wait(&mut self) -> Result<(), Error<E>> {
-        self.busy.wait_for_low().await.ok();
-        Ok(())
+    pub fn power_state(&self) -> PowerState {
+        self.power
     }
+}
 
-    pub async fn power_up<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
-    where
-        D: DelayUs,
-    {
-        self.hardware_reset(delay).await?;
-        self.software_reset().await?;
+/// Command sequences of `SpiInterface`, expanded in an impl block of
+/// `SpiInterface<Bus<..>, SIZE>` as `sequences!(async; DelayUs; Error<..>)`
+/// or `sequences!(blocking; DelayMs<u32>; Error<..>)`. `read` adds
+/// `read_temperature`, for buses able to read back.
+macro_rules! sequences {
+    (async; $delay:path; $error:ty) => {
+        $crate::interface::sequences!(@write [async] [.await] $delay; $error);
+    };
+    (blocking; $delay:path; $error:ty) => {
+        $crate::interface::sequences!(@write [] [] $delay; $error);
+    };
+    (read async; $delay:path; $error:ty) => {
+        $crate::interface::sequences!(@read [async] [.await] $delay; $error);
+    };
+    (read blocking; $delay:path; $error:ty) => {
+        $crate::interface::sequences!(@read [] [] $delay; $error);
+    };
 
-        // command list
+    (@write [$($async:tt)*] [$($await:tt)*] $delay:path; $error:ty) => {
+        pub $($async)* fn write_waveform(
+            &mut self,
+            waveform: &$crate::waveform::Waveform,
+        ) -> Result<(), $error> {
+            self.send_commands(&waveform.commands())$($await)*
+        }
+
+        pub $($async)* fn software_reset<D>(&mut self, delay: &mut D) -> Result<(), $error>
+        where
+            D: $delay,
         {
-            self.send_command(Command::DataMode).await?;
-            self.send_data(&[0x03]).await?;
+            self.bus.send_command($crate::command::Command::Reset)$($await)*?;
+            self.busy_wait(delay, $crate::command::RESET_BUSY_MS)$($await)*
+        }
+
+        pub $($async)* fn write_ram_frame_buffer(
+            &mut self,
+            buffer: &[u8],
+            command: $crate::command::Command,
+        ) -> Result<(), $error> {
+            self.bus.send_command(command)$($await)*?;
+            self.bus.send_data(buffer)$($await)*
+        }
 
-            self.send_command(Command::Border).await?;
-            self.send_data(&[0x05]).await?;
+        /// Write the `columns` bytes of each of the `rows` lines of `buffer`,
+        /// starting at the matching ram address. The ram window must already
+        /// cover the area.
+        pub $($async)* fn write_ram_area(
+            &mut self,
+            buffer: &[u8],
+            columns: core::ops::RangeInclusive<usize>,
+            rows: core::ops::RangeInclusive<usize>,
+            command: $crate::command::Command,
+        ) -> Result<(), $error> {
+            self.set_ram_address(
+                *columns.start() as u8 + SIZE::X_OFFSET,
+                *rows.start() as u16,
+            )$($await)*?;
 
-            self.send_command(Command::Vcom).await?;
-            self.send_data(&[0x36]).await?;
+            self.bus.send_command(command)$($await)*?;
+            for row in rows {
+                let start = row * SIZE::STRIDE;
+                self.bus
+                    .send_data(&buffer[start + columns.start()..=start + columns.end()])
+                    $($await)*?;
+            }
+            Ok(())
+        }
 
-            self.send_command(Command::GateVoltage).await?;
-            self.send_data(&[0x17]).await?;
+        /// Point the address counter at byte `x` of framebuffer row `row`
+        pub $($async)* fn set_ram_address(&mut self, x: u8, row: u16) -> Result<(), $error> {
+            use $crate::command::{gate_address, Command};
 
-            self.send_command(Command::SourceVoltage).await?;
-            self.send_data(&[0x41, 0x00, 0x32]).await?;
+            self.bus.send_command(Command::RamXCount)$($await)*?;
+            self.bus.send_data(&[x])$($await)*?;
 
-            self.set_ram_address(1, 0).await?;
+            self.bus.send_command(Command::RamYCount)$($await)*?;
+            self.bus
+                .send_data(&gate_address(self.data_entry.gate(row, SIZE::WIDTH)))
+                $($await)*
         }
 
-        self.send_command(Command::RamXPos).await?;
-        self.send_data(&[0x01, ((DISPLAY_HEIGHT + 7) / 8)]).await?;
+        /// Limit the address counter to bytes `x_start..=x_end` of framebuffer
+        /// rows `row_start..=row_end`
+        pub $($async)* fn set_ram_window(
+            &mut self,
+            x_start: u8,
+            x_end: u8,
+            row_start: u16,
+            row_end: u16,
+        ) -> Result<(), $error> {
+            use $crate::command::{gate_address, Command};
 
-        self.send_command(Command::RamYPos).await?;
-        self.send_data(&[0, 0, DISPLAY_WIDTH - 1, ((DISPLAY_WIDTH + 7) / 8)])
-            .await?;
+            // in Y decrement mode the window starts on the higher gate
+            let [y_start_lo, y_start_hi] =
+                gate_address(self.data_entry.gate(row_start, SIZE::WIDTH));
+            let [y_end_lo, y_end_hi] = gate_address(self.data_entry.gate(row_end, SIZE::WIDTH));
 
-        self.send_command(Command::Control).await?;
-        self.send_data(&[DISPLAY_WIDTH - 1, ((DISPLAY_WIDTH + 7) / 8), 0])
-            .await
-    }
+            self.bus.send_command(Command::RamXPos)$($await)*?;
+            self.bus.send_data(&[x_start, x_end])$($await)*?;
 
-    pub async fn power_down(&mut self) -> Result<(), Error<E>> {
-        self.send_command(Command::Sleep).await?;
-        self.send_data(&[0x01]).await
-    }
+            self.bus.send_command(Command::RamYPos)$($await)*?;
+            self.bus
+                .send_data(&[y_start_lo, y_start_hi, y_end_lo, y_end_hi])
+                $($await)*
+        }
+
+        /// Open the ram window over the whole panel
+        pub $($async)* fn set_full_window(&mut self) -> Result<(), $error> {
+            let last_x = SIZE::X_OFFSET + SIZE::STRIDE as u8 - 1;
+            self.set_ram_window(SIZE::X_OFFSET, last_x, 0, SIZE::WIDTH - 1)$($await)*
+        }
+
+        pub $($async)* fn update<D>(
+            &mut self,
+            mode: $crate::command::UpdateMode,
+            delay: &mut D,
+        ) -> Result<(), $error>
+        where
+            D: $delay,
+        {
+            self.begin_update(mode, delay)$($await)*?;
+            self.wait_update_complete(delay)$($await)*
+        }
+
+        /// Start a display update and return once `MasterActivate` is sent,
+        /// after waiting for the previous one
+        pub $($async)* fn begin_update<D>(
+            &mut self,
+            mode: $crate::command::UpdateMode,
+            delay: &mut D,
+        ) -> Result<(), $error>
+        where
+            D: $delay,
+        {
+            use $crate::command::{temperature_register, Command, UpdateMode};
+
+            self.wake(delay)$($await)*?;
+            self.wait_update_complete(delay)$($await)*?;
+
+            // selects the OTP waveform, unless a custom one is loaded
+            if let Some(celsius) = self.temperature {
+                self.bus.send_command(Command::TempWrite)$($await)*?;
+                self.bus.send_data(&temperature_register(celsius))$($await)*?;
+            }
+
+            let control = mode.control(self.waveform.is_some(), self.temperature.is_some());
+            self.bus.send_command(Command::DispCtrl2)$($await)*?;
+            self.bus.send_data(&[control])$($await)*?;
+
+            let busy_ms = match mode {
+                UpdateMode::Full => self.update_busy_ms,
+                UpdateMode::Partial => $crate::command::PARTIAL_UPDATE_BUSY_MS,
+            };
+
+            self.bus.send_command(Command::MasterActivate)$($await)*?;
+            self.updating = Some(busy_ms);
+            Ok(())
+        }
+
+        /// Wait for the update started by `begin_update`, returns at once when
+        /// there is none. The update stays pending if the wait fails or, for
+        /// the async interface, is dropped.
+        pub $($async)* fn wait_update_complete<D>(&mut self, delay: &mut D) -> Result<(), $error>
+        where
+            D: $delay,
+        {
+            if let Some(busy_ms) = self.updating {
+                self.busy_wait(delay, busy_ms)$($await)*?;
+                self.updating = None;
+            }
+            Ok(())
+        }
+
+        /// Power up unless the controller is already awake, then program a data
+        /// entry mode or waveform changed in the meantime
+        pub $($async)* fn wake<D>(&mut self, delay: &mut D) -> Result<(), $error>
+        where
+            D: $delay,
+        {
+            if self.power != $crate::command::PowerState::Awake {
+                return self.power_up(delay)$($await)*;
+            }
+
+            if self.data_entry_changed {
+                self.wait_update_complete(delay)$($await)*?;
+
+                self.bus.send_command($crate::command::Command::DataMode)$($await)*?;
+                self.bus.send_data(&[self.data_entry as u8])$($await)*?;
+                // the Y window runs the other way round
+                self.set_full_window()$($await)*?;
+                self.data_entry_changed = false;
+            }
+
+            if self.waveform_changed {
+                self.wait_update_complete(delay)$($await)*?;
+
+                // without one the update control loads the OTP waveform
+                if let Some(waveform) = self.waveform {
+                    self.write_waveform(&waveform)$($await)*?;
+                }
+                self.waveform_changed = false;
+            }
+            Ok(())
+        }
+
+        /// Poll the busy pin until it goes low, giving up after the busy
+        /// timeout. Without a busy pin waits `busy_ms`, the worst case for the
+        /// operation.
+        pub $($async)* fn busy_wait<D>(&mut self, delay: &mut D, busy_ms: u32) -> Result<(), $error>
+        where
+            D: $delay,
+        {
+            self.bus
+                .busy_wait(delay, busy_ms, self.busy_timeout_ms)
+                $($await)*
+        }
+
+        pub $($async)* fn send_commands(
+            &mut self,
+            commands: &[($crate::command::Command, &[u8])],
+        ) -> Result<(), $error> {
+            for &(command, data) in commands {
+                self.bus.send_command(command)$($await)*?;
+                self.bus.send_data(data)$($await)*?;
+            }
+            Ok(())
+        }
+
+        pub $($async)* fn power_up<D>(&mut self, delay: &mut D) -> Result<(), $error>
+        where
+            D: $delay,
+        {
+            use $crate::command::{gate_address, Command, PowerState, INIT_SEQUENCE};
+
+            // a reset would cut the running update short
+            self.wait_update_complete(delay)$($await)*?;
+
+            self.bus.hardware_reset(delay)$($await)*?;
+            self.power = PowerState::Reset;
+            self.software_reset(delay)$($await)*?;
+
+            // command list
+            {
+                self.bus.send_command(Command::DataMode)$($await)*?;
+                self.bus.send_data(&[self.data_entry as u8])$($await)*?;
+
+                self.send_commands(&INIT_SEQUENCE)$($await)*?;
+
+                self.bus.send_command(Command::TempSensor)$($await)*?;
+                self.bus.send_data(&[self.temperature_sensor as u8])$($await)*?;
+
+                self.set_ram_address(SIZE::X_OFFSET, 0)$($await)*?;
+            }
+
+            self.set_full_window()$($await)*?;
+
+            // MUX is the number of gate lines minus one
+            let [mux_lo, mux_hi] = gate_address(SIZE::WIDTH - 1);
+            self.bus.send_command(Command::Control)$($await)*?;
+            self.bus.send_data(&[mux_lo, mux_hi, 0])$($await)*?;
+
+            if let Some(waveform) = self.waveform {
+                self.write_waveform(&waveform)$($await)*?;
+            }
+
+            self.power = PowerState::Awake;
+            self.data_entry_changed = false;
+            self.waveform_changed = false;
+            Ok(())
+        }
+
+        /// Enter deep sleep. Only a hardware reset wakes the controller, so
+        /// without a reset pin it is left in standby instead. Fails with
+        /// [`Error::Busy`](crate::Error::Busy) while an update is pending.
+        pub $($async)* fn power_down(&mut self) -> Result<(), $error> {
+            use $crate::command::{Command, PowerState};
+
+            if self.bus.reset.is_none() || self.power == PowerState::DeepSleep {
+                return Ok(());
+            }
+            if self.updating.is_some() {
+                return Err($crate::Error::Busy);
+            }
+
+            self.bus.send_command(Command::Sleep)$($await)*?;
+            self.bus.send_data(&[0x01])$($await)*?;
+            self.power = PowerState::DeepSleep;
+            Ok(())
+        }
+    };
+
+    (@read [$($async:tt)*] [$($await:tt)*] $delay:path; $error:ty) => {
+        /// Load the internal sensor into the temperature register and read it
+        /// back in degrees Celsius, then restore the sensor selection
+        pub $($async)* fn read_temperature<D>(&mut self, delay: &mut D) -> Result<f32, $error>
+        where
+            D: $delay,
+        {
+            use $crate::command::{
+                temperature, Command, TemperatureSensor, LOAD_TEMPERATURE, TEMPERATURE_BUSY_MS,
+            };
+
+            // loading the sensor would cut the running update short
+            self.wait_update_complete(delay)$($await)*?;
+
+            self.bus.send_command(Command::TempSensor)$($await)*?;
+            self.bus.send_data(&[TemperatureSensor::Internal as u8])$($await)*?;
+
+            self.bus.send_command(Command::DispCtrl2)$($await)*?;
+            self.bus.send_data(&[LOAD_TEMPERATURE])$($await)*?;
+
+            self.bus.send_command(Command::MasterActivate)$($await)*?;
+            self.busy_wait(delay, TEMPERATURE_BUSY_MS)$($await)*?;
+
+            self.bus.send_command(Command::TempRead)$($await)*?;
+            let mut bytes = [0; 2];
+            self.bus.read_data(&mut bytes)$($await)*?;
+
+            self.bus.send_command(Command::TempSensor)$($await)*?;
+            self.bus.send_data(&[self.temperature_sensor as u8])$($await)*?;
+
+            // the load also fetched the OTP waveform into the LUT register
+            if self.waveform.is_some() {
+                self.waveform_changed = true;
+            }
+            Ok(temperature(bytes))
+        }
+    };
 }
+
+pub(crate) use sequences;
@@ -1,4 +1,4 @@
-#![feature(async_closure)]
+#![cfg_attr(feature = "async", feature(async_closure))]
 #![no_std]
 // #![deny(missing_debug_implementations)]
 // #![deny(missing_docs)]
@@ -11,53 +11,133 @@
 // #![deny(unused_import_braces)]
 // #![deny(unused_qualifications)]
 
-const DISPLAY_WIDTH: u8 = 250;
-const DISPLAY_HEIGHT: u8 = 122;
-// round up to divisible by 8
-const BUF_SIZE: usize = ((DISPLAY_HEIGHT as usize + 7) / 8) * DISPLAY_WIDTH as usize;
+#[cfg(feature = "std")]
+extern crate std;
 
 pub use crate::{
-    display::DisplayRotation, error::Error, ssd1680::Ssd1680, ssd1680tricolor::Ssd1680TriColor,
-    ssd1680tricolor::TriColor,
+    color::TriColor,
+    command::{Command, PowerState, TemperatureSensor, DEFAULT_BUSY_TIMEOUT_MS},
+    display::DisplayRotation,
+    error::{Error, OutOfBounds, PinError},
+    framebuffer::{Framebuffer, TriColorFramebuffer},
+    pin::NoPin,
+    size::{DisplaySize, DisplaySize250x122, DisplaySize296x128, DisplaySize296x152},
+    waveform::Waveform,
 };
 
+#[cfg(feature = "async")]
+pub use crate::asynch::{Ssd1680, Ssd1680TriColor};
+
+#[cfg(feature = "async")]
+mod asynch;
+pub mod blocking;
+mod color;
+mod command;
 mod display;
+#[cfg(feature = "graphics")]
+pub mod dither;
+mod driver;
+pub mod framebuffer;
 mod interface;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "graphics")]
+pub mod preview;
+pub mod size;
 mod ssd1680;
 mod ssd1680tricolor;
+pub mod waveform;
+
+mod error {
+    use core::convert::Infallible;
 
-mod command {
+    #[derive(Debug)]
+    pub enum Error<E = (), PE = PinError> {
+        /// Communication error
+        Comm(E),
+        /// Pin error, telling which pin failed
+        Pin(PE),
+        /// The busy pin was not released within the busy timeout
+        BusyTimeout,
+        /// A display update is still running, wait for it with
+        /// `wait_update_complete` first
+        Busy,
+    }
 
-    /// SSD1680 Commands
+    /// Error of the dc, reset or busy pin, each with the pin's own error type
     #[derive(Debug)]
-    #[allow(dead_code)]
-    pub enum Command {
-        /// SW Reset
-        Reset = 0x12,
-        DataMode = 0x11,
-        Border = 0x3C,
-        GateVoltage = 0x03,
-        SourceVoltage = 0x04,
-        RamXCount = 0x4E,
-        RamYCount = 0x4F,
-        RamXPos = 0x44,
-        RamYPos = 0x45,
-        Control = 0x01,
-        Vcom = 0x2C,
-        DispCtrl1 = 0x21,
-        DispCtrl2 = 0x22,
-        MasterActivate = 0x20,
-        WriteRAM1 = 0x24,
-        WriteRAM2 = 0x26,
-        Sleep = 0x10,
+    pub enum PinError<DE = Infallible, RE = Infallible, BE = Infallible> {
+        Dc(DE),
+        Reset(RE),
+        Busy(BE),
+    }
+
+    impl<E, DE, RE, BE> Error<E, PinError<DE, RE, BE>> {
+        pub(crate) fn dc(error: DE) -> Self {
+            Error::Pin(PinError::Dc(error))
+        }
+
+        pub(crate) fn reset(error: RE) -> Self {
+            Error::Pin(PinError::Reset(error))
+        }
+
+        pub(crate) fn busy(error: BE) -> Self {
+            Error::Pin(PinError::Busy(error))
+        }
+    }
+
+    /// A pixel was drawn off the panel
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct OutOfBounds {
+        pub x: i32,
+        pub y: i32,
     }
 }
 
-mod error {
+mod pin {
+    use core::convert::Infallible;
+    use embedded_hal::digital::v2::{InputPin, OutputPin};
 
-    #[derive(Debug)]
-    pub enum Error<E = ()> {
-        /// Communication error
-        Comm(E),
+    /// Stands in for a reset or busy pin that is not wired, to name the pin
+    /// type of a `None`, e.g. `None::<NoPin>`
+    #[derive(Clone, Copy, Debug)]
+    pub struct NoPin;
+
+    impl OutputPin for NoPin {
+        type Error = Infallible;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    impl InputPin for NoPin {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            Ok(false)
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            Ok(true)
+        }
+    }
+
+    /// Never busy, the async drivers wait out the worst case time instead
+    #[cfg(feature = "async")]
+    impl embedded_hal_async::digital::Wait for NoPin {
+        type Error = Infallible;
+
+        async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
     }
 }
@@ -0,0 +1,114 @@
+//! `embedded-hal-async` impls of the mocks, driving the async drivers.
+//!
+//! Time is counted in polls: a delay of `ms` stays pending for `ms` polls and
+//! the busy pin stays high for the polls set with
+//! [`Mock::set_busy_polls`](super::Mock::set_busy_polls), so a busy wait and
+//! its timeout race like they would on a device.
+
+use super::{Event, MockBusy, MockDelay, MockSpi};
+use core::convert::Infallible;
+use core::future::{poll_fn, Future};
+use core::pin::pin;
+use core::ptr;
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use embedded_hal_async::delay::DelayUs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::spi::{ErrorType, SpiBus, SpiDevice};
+
+/// Run `future` to completion on the current thread, polling it until it is
+/// ready. Enough for the mocks, which never wait on anything outside the
+/// poll loop.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = noop_waker();
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+fn noop_waker() -> Waker {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+    const RAW: RawWaker = RawWaker::new(ptr::null(), &VTABLE);
+
+    // SAFETY: the vtable functions do nothing and never touch the data pointer
+    unsafe { Waker::from_raw(RAW) }
+}
+
+/// Pending for `polls` polls, then ready
+async fn polls(mut polls: u32) {
+    poll_fn(|cx| {
+        if polls == 0 {
+            return Poll::Ready(());
+        }
+        polls -= 1;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+    .await
+}
+
+impl ErrorType for MockSpi {
+    type Error = Infallible;
+}
+
+impl SpiBus for MockSpi {}
+
+impl SpiDevice for MockSpi {
+    type Bus = MockSpi;
+
+    async fn write(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.record_write(buf);
+        Ok(())
+    }
+
+    async fn read(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.record_read(buf);
+        Ok(())
+    }
+}
+
+impl Wait for MockBusy {
+    type Error = Infallible;
+
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        poll_fn(|cx| {
+            if self.0.state.borrow().busy_polls > 0 {
+                return Poll::Ready(Ok(()));
+            }
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        })
+        .await
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        poll_fn(|cx| {
+            let mut state = self.0.state.borrow_mut();
+            if state.busy_polls == 0 {
+                return Poll::Ready(Ok(()));
+            }
+            state.busy_polls -= 1;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+impl DelayUs for MockDelay {
+    type Error = Infallible;
+
+    async fn delay_us(&mut self, us: u32) -> Result<(), Self::Error> {
+        self.delay_ms(us.div_ceil(1000)).await
+    }
+
+    async fn delay_ms(&mut self, ms: u32) -> Result<(), Self::Error> {
+        self.0.record(Event::Delay(ms));
+        polls(ms).await;
+        Ok(())
+    }
+}
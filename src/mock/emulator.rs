@@ -0,0 +1,240 @@
+use super::Event;
+use crate::{color::TriColor, command::Command, size::DisplaySize};
+use core::marker::PhantomData;
+use embedded_graphics_core::pixelcolor::BinaryColor;
+use std::{vec, vec::Vec};
+
+/// Bytes in a RAM row, the controller has 176 sources
+const RAM_COLUMNS: u16 = 22;
+/// RAM rows, the controller has 296 gates
+const RAM_ROWS: u16 = 296;
+
+/// Data entry mode after a reset, X and Y increment
+const DEFAULT_DATA_ENTRY: u8 = 0x03;
+
+/// Model of the controller interpreting the recorded command stream: RAM
+/// windows, address counters, data entry mode, RAM1 and RAM2 writes, deep
+/// sleep and the image latched by each display update.
+///
+/// Pixels are addressed physically, by gate and source, the way the panel
+/// is wired with `SIZE::X_OFFSET`.
+#[derive(Clone, Debug)]
+pub struct Emulator<SIZE> {
+    ram1: Vec<u8>,
+    ram2: Vec<u8>,
+    displayed1: Vec<u8>,
+    displayed2: Vec<u8>,
+    data_entry: u8,
+    x_window: (u16, u16),
+    y_window: (u16, u16),
+    x: u16,
+    y: u16,
+    update_control: u8,
+    updates: usize,
+    asleep: bool,
+    command: Option<Command>,
+    parameters: Vec<u8>,
+    size: PhantomData<SIZE>,
+}
+
+impl<SIZE> Default for Emulator<SIZE>
+where
+    SIZE: DisplaySize,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<SIZE> Emulator<SIZE>
+where
+    SIZE: DisplaySize,
+{
+    /// Controller after power on, RAM and panel cleared to black
+    pub fn new() -> Self {
+        let ram = vec![0; RAM_COLUMNS as usize * RAM_ROWS as usize];
+        Self {
+            ram1: ram.clone(),
+            ram2: ram.clone(),
+            displayed1: ram.clone(),
+            displayed2: ram,
+            data_entry: DEFAULT_DATA_ENTRY,
+            x_window: (0, RAM_COLUMNS - 1),
+            y_window: (0, RAM_ROWS - 1),
+            x: 0,
+            y: 0,
+            update_control: 0,
+            updates: 0,
+            asleep: false,
+            command: None,
+            parameters: Vec::new(),
+            size: PhantomData,
+        }
+    }
+
+    /// Interpret `events`, in the order the driver caused them
+    pub fn feed(&mut self, events: &[Event]) {
+        for event in events {
+            match event {
+                Event::Reset(false) => self.hardware_reset(),
+                Event::Reset(true) | Event::Read(_) | Event::Delay(_) => {}
+                _ if self.asleep => {}
+                Event::Command(bytes) => {
+                    for &byte in bytes {
+                        self.finish_command();
+                        self.command = Command::try_from(byte).ok();
+                    }
+                }
+                Event::Data(bytes) => match self.command {
+                    Some(Command::WriteRAM1) => bytes.iter().for_each(|&byte| self.write(byte, 1)),
+                    Some(Command::WriteRAM2) => bytes.iter().for_each(|&byte| self.write(byte, 2)),
+                    _ => self.parameters.extend(bytes),
+                },
+            }
+        }
+
+        // the last command has no following one to end it
+        self.finish_command();
+    }
+
+    /// Display updates run so far
+    pub fn updates(&self) -> usize {
+        self.updates
+    }
+
+    /// Whether the controller is in deep sleep, waiting for a hardware reset
+    pub fn is_asleep(&self) -> bool {
+        self.asleep
+    }
+
+    /// Monochrome panel pixel at `gate` and `source` after the last update,
+    /// `On` is black like in [`Framebuffer`](crate::Framebuffer)
+    pub fn displayed(&self, gate: u16, source: u16) -> BinaryColor {
+        if self.bit(&self.displayed1, gate, source) {
+            BinaryColor::Off
+        } else {
+            BinaryColor::On
+        }
+    }
+
+    /// Tricolor panel pixel at `gate` and `source` after the last update
+    pub fn displayed_tricolor(&self, gate: u16, source: u16) -> TriColor {
+        if self.bit(&self.displayed2, gate, source) {
+            TriColor::Chromatic
+        } else if self.bit(&self.displayed1, gate, source) {
+            TriColor::White
+        } else {
+            TriColor::Black
+        }
+    }
+
+    /// RAM1 and RAM2 bits at `gate` and `source`, whether or not they have
+    /// been displayed yet
+    pub fn ram(&self, gate: u16, source: u16) -> (bool, bool) {
+        (
+            self.bit(&self.ram1, gate, source),
+            self.bit(&self.ram2, gate, source),
+        )
+    }
+
+    fn bit(&self, ram: &[u8], gate: u16, source: u16) -> bool {
+        assert!(
+            gate < SIZE::WIDTH && source < SIZE::HEIGHT,
+            "pixel ({gate}, {source}) is off the panel"
+        );
+
+        let x = SIZE::X_OFFSET as u16 + source / 8;
+        ram[(gate * RAM_COLUMNS + x) as usize] & (0x80 >> (source % 8)) != 0
+    }
+
+    fn hardware_reset(&mut self) {
+        self.asleep = false;
+        self.command = None;
+        self.parameters.clear();
+        self.reset_registers();
+    }
+
+    /// Registers back to their reset values, RAM is kept
+    fn reset_registers(&mut self) {
+        self.data_entry = DEFAULT_DATA_ENTRY;
+        self.x_window = (0, RAM_COLUMNS - 1);
+        self.y_window = (0, RAM_ROWS - 1);
+        self.x = 0;
+        self.y = 0;
+    }
+
+    /// Apply the parameters of the current command once all of them arrived
+    fn finish_command(&mut self) {
+        let parameters = core::mem::take(&mut self.parameters);
+
+        match (self.command.take(), parameters.as_slice()) {
+            (Some(Command::Reset), _) => self.reset_registers(),
+            (Some(Command::DataMode), &[mode, ..]) => self.data_entry = mode & 0x07,
+            (Some(Command::RamXPos), &[start, end, ..]) => {
+                self.x_window = (start as u16 & 0x3F, end as u16 & 0x3F)
+            }
+            (Some(Command::RamYPos), &[start_lo, start_hi, end_lo, end_hi, ..]) => {
+                self.y_window = (address(start_lo, start_hi), address(end_lo, end_hi))
+            }
+            (Some(Command::RamXCount), &[x, ..]) => self.x = x as u16 & 0x3F,
+            (Some(Command::RamYCount), &[lo, hi, ..]) => self.y = address(lo, hi),
+            (Some(Command::DispCtrl2), &[control, ..]) => self.update_control = control,
+            (Some(Command::MasterActivate), _) => self.activate(),
+            (Some(Command::Sleep), &[mode, ..]) => self.asleep = mode & 0x03 != 0,
+            _ => {}
+        }
+    }
+
+    fn activate(&mut self) {
+        // only the display step changes what the panel shows
+        if self.update_control & 0x04 != 0 {
+            self.displayed1.copy_from_slice(&self.ram1);
+            self.displayed2.copy_from_slice(&self.ram2);
+            self.updates += 1;
+        }
+    }
+
+    fn write(&mut self, byte: u8, plane: u8) {
+        if self.x < RAM_COLUMNS && self.y < RAM_ROWS {
+            let index = (self.y * RAM_COLUMNS + self.x) as usize;
+            match plane {
+                1 => self.ram1[index] = byte,
+                _ => self.ram2[index] = byte,
+            }
+        }
+
+        let x_increment = self.data_entry & 0x01 != 0;
+        let y_increment = self.data_entry & 0x02 != 0;
+
+        // AM picks the counter that moves first, the other one moves when it
+        // wraps around its window
+        if self.data_entry & 0x04 == 0 {
+            if step(&mut self.x, self.x_window, x_increment) {
+                step(&mut self.y, self.y_window, y_increment);
+            }
+        } else if step(&mut self.y, self.y_window, y_increment) {
+            step(&mut self.x, self.x_window, x_increment);
+        }
+    }
+}
+
+/// 9 bit gate address
+fn address(lo: u8, hi: u8) -> u16 {
+    (hi as u16 & 0x01) << 8 | lo as u16
+}
+
+/// Move `counter` one step, back to the start of the window after its end.
+/// Returns whether it wrapped around.
+fn step(counter: &mut u16, (start, end): (u16, u16), increment: bool) -> bool {
+    if *counter == end {
+        *counter = start;
+        return true;
+    }
+
+    *counter = if increment {
+        counter.wrapping_add(1)
+    } else {
+        counter.wrapping_sub(1)
+    };
+    false
+}
@@ -0,0 +1,286 @@
+//! Mock SPI, pins and delay recording everything a driver does, so command
+//! sequences can be checked in host tests without a panel.
+//!
+//! The mocks implement the `embedded-hal` blocking traits, so they drive the
+//! [`blocking`](crate::blocking) drivers, and with the `async` feature the
+//! `embedded-hal-async` ones, run with [`block_on`]. Every handle shares one
+//! [`Mock`] log.
+//!
+//! ```
+//! use ssd1680::{blocking::Ssd1680, mock::Mock, Command, DisplayRotation, DisplaySize250x122};
+//!
+//! let mock = Mock::new();
+//! let mut display = Ssd1680::new(
+//!     mock.spi(),
+//!     mock.dc(),
+//!     Some(mock.reset()),
+//!     Some(mock.busy()),
+//!     DisplaySize250x122,
+//!     DisplayRotation::Rotate0,
+//! );
+//!
+//! display.power_down().unwrap();
+//!
+//! let transactions = mock.transactions().unwrap();
+//! assert_eq!(transactions[0].command, Command::Sleep);
+//! assert_eq!(transactions[0].data, [0x01]);
+//! ```
+//!
+//! [`Emulator`] replays the log through a model of the controller, to check
+//! what the panel would show.
+
+pub use self::emulator::Emulator;
+
+#[cfg(feature = "async")]
+pub use self::asynch::block_on;
+
+use crate::command::Command;
+use core::convert::Infallible;
+use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::blocking::spi::{Transfer, Write};
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+use std::{cell::RefCell, collections::VecDeque, rc::Rc, vec::Vec};
+
+#[cfg(feature = "async")]
+mod asynch;
+mod emulator;
+
+/// Something a driver did to the mocks
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// Bytes written with the DC pin low
+    Command(Vec<u8>),
+    /// Bytes written with the DC pin high
+    Data(Vec<u8>),
+    /// Bytes read back over SPI
+    Read(Vec<u8>),
+    /// Level the reset pin was set to
+    Reset(bool),
+    /// Delay in milliseconds
+    Delay(u32),
+}
+
+/// A command and the data written after it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transaction {
+    pub command: Command,
+    pub data: Vec<u8>,
+}
+
+/// Why a recorded byte stream isn't a sequence of commands
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Byte written with the DC pin low that isn't a [`Command`]
+    UnknownCommand(u8),
+    /// Data written before any command
+    DataWithoutCommand,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    events: Vec<Event>,
+    dc: bool,
+    busy_polls: usize,
+    reads: VecDeque<u8>,
+}
+
+/// Shared log of the mocks, hands out the SPI, pin and delay mocks
+#[derive(Clone, Debug, Default)]
+pub struct Mock {
+    state: Rc<RefCell<State>>,
+}
+
+impl Mock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn spi(&self) -> MockSpi {
+        MockSpi(self.clone())
+    }
+
+    pub fn dc(&self) -> MockDc {
+        MockDc(self.clone())
+    }
+
+    pub fn reset(&self) -> MockReset {
+        MockReset(self.clone())
+    }
+
+    pub fn busy(&self) -> MockBusy {
+        MockBusy(self.clone())
+    }
+
+    pub fn delay(&self) -> MockDelay {
+        MockDelay(self.clone())
+    }
+
+    /// Keep the busy pin high for the next `polls` reads
+    pub fn set_busy_polls(&self, polls: usize) {
+        self.state.borrow_mut().busy_polls = polls;
+    }
+
+    /// Bytes returned by the following SPI reads, zeros once they run out
+    pub fn queue_read(&self, bytes: &[u8]) {
+        self.state.borrow_mut().reads.extend(bytes);
+    }
+
+    /// Everything recorded so far
+    pub fn events(&self) -> Vec<Event> {
+        self.state.borrow().events.clone()
+    }
+
+    /// Forget everything recorded so far
+    pub fn clear(&self) {
+        self.state.borrow_mut().events.clear();
+    }
+
+    /// The recorded SPI writes decoded into commands
+    pub fn transactions(&self) -> Result<Vec<Transaction>, DecodeError> {
+        decode(&self.state.borrow().events)
+    }
+
+    fn record(&self, event: Event) {
+        let mut state = self.state.borrow_mut();
+
+        // the drivers write a buffer in chunks, merge them back
+        match (state.events.last_mut(), event) {
+            (Some(Event::Data(data)), Event::Data(bytes)) => data.extend(bytes),
+            (_, event) => state.events.push(event),
+        }
+    }
+}
+
+/// Decode SPI writes into commands and their data, skipping pin and delay
+/// events
+pub fn decode(events: &[Event]) -> Result<Vec<Transaction>, DecodeError> {
+    let mut transactions: Vec<Transaction> = Vec::new();
+
+    for event in events {
+        match event {
+            Event::Command(bytes) => {
+                for &byte in bytes {
+                    let command = Command::try_from(byte).map_err(DecodeError::UnknownCommand)?;
+                    transactions.push(Transaction {
+                        command,
+                        data: Vec::new(),
+                    });
+                }
+            }
+            Event::Data(bytes) => transactions
+                .last_mut()
+                .ok_or(DecodeError::DataWithoutCommand)?
+                .data
+                .extend(bytes),
+            Event::Read(_) | Event::Reset(_) | Event::Delay(_) => {}
+        }
+    }
+
+    Ok(transactions)
+}
+
+/// SPI tagging every write with the level of the [`MockDc`] pin
+#[derive(Debug)]
+pub struct MockSpi(Mock);
+
+impl MockSpi {
+    fn record_write(&self, words: &[u8]) {
+        let event = if self.0.state.borrow().dc {
+            Event::Data(words.to_vec())
+        } else {
+            Event::Command(words.to_vec())
+        };
+        self.0.record(event);
+    }
+
+    /// Fill `words` from the queued reads
+    fn record_read(&self, words: &mut [u8]) {
+        {
+            let mut state = self.0.state.borrow_mut();
+            for word in words.iter_mut() {
+                *word = state.reads.pop_front().unwrap_or(0);
+            }
+        }
+        self.0.record(Event::Read(words.to_vec()));
+    }
+}
+
+impl Write<u8> for MockSpi {
+    type Error = Infallible;
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.record_write(words);
+        Ok(())
+    }
+}
+
+impl Transfer<u8> for MockSpi {
+    type Error = Infallible;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        self.record_read(words);
+        Ok(words)
+    }
+}
+
+#[derive(Debug)]
+pub struct MockDc(Mock);
+
+impl OutputPin for MockDc {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.state.borrow_mut().dc = false;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.state.borrow_mut().dc = true;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct MockReset(Mock);
+
+impl OutputPin for MockReset {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.record(Event::Reset(false));
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.record(Event::Reset(true));
+        Ok(())
+    }
+}
+
+/// Busy pin, idle unless [`Mock::set_busy_polls`] says otherwise
+#[derive(Debug)]
+pub struct MockBusy(Mock);
+
+impl InputPin for MockBusy {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        let mut state = self.0.state.borrow_mut();
+        let busy = state.busy_polls > 0;
+        state.busy_polls = state.busy_polls.saturating_sub(1);
+        Ok(busy)
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        self.is_high().map(|high| !high)
+    }
+}
+
+#[derive(Debug)]
+pub struct MockDelay(Mock);
+
+impl DelayMs<u32> for MockDelay {
+    fn delay_ms(&mut self, ms: u32) {
+        self.0.record(Event::Delay(ms));
+    }
+}
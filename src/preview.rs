@@ -0,0 +1,167 @@
+//! Previews of exactly what the panel shows, rendered from the RAM encoded
+//! frame buffers onto any `Rgb888` draw target, like an
+//! `embedded-graphics-simulator` `SimulatorDisplay<Rgb888>`.
+//!
+//! ```ignore
+//! let mut preview = SimulatorDisplay::<Rgb888>::new(framebuffer.size());
+//! framebuffer.draw_preview(&mut preview, Rgb888::RED)?;
+//! window.update(&preview);
+//! ```
+
+use crate::{
+    color::TriColor,
+    framebuffer::{Framebuffer, TriColorFramebuffer},
+    size::DisplaySize,
+};
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    pixelcolor::{BinaryColor, Rgb888, RgbColor},
+    prelude::*,
+    primitives::Rectangle,
+};
+
+impl<SIZE> Framebuffer<SIZE>
+where
+    SIZE: DisplaySize,
+{
+    /// Draw the frame onto `target` in logical orientation, black and white
+    pub fn draw_preview<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb888>,
+    {
+        let area = Rectangle::new(Point::zero(), self.size());
+
+        target.fill_contiguous(
+            &area,
+            area.points()
+                .map(|point| match self.pixel(point.x, point.y) {
+                    Some(BinaryColor::On) => Rgb888::BLACK,
+                    _ => Rgb888::WHITE,
+                }),
+        )
+    }
+}
+
+impl<SIZE> TriColorFramebuffer<SIZE>
+where
+    SIZE: DisplaySize,
+{
+    /// Draw both planes composited onto `target` in logical orientation,
+    /// chromatic pixels in `chromatic`, the red or yellow of the panel
+    pub fn draw_preview<D>(&self, target: &mut D, chromatic: Rgb888) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb888>,
+    {
+        let area = Rectangle::new(Point::zero(), self.size());
+
+        target.fill_contiguous(
+            &area,
+            area.points()
+                .map(|point| match self.pixel(point.x, point.y) {
+                    Some(TriColor::Black) => Rgb888::BLACK,
+                    Some(TriColor::Chromatic) => chromatic,
+                    _ => Rgb888::WHITE,
+                }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{display::DisplayRotation, size::TestSize};
+    use core::convert::Infallible;
+
+    /// Rgb888 target in portrait, the size of the rotated test panel
+    struct Canvas {
+        pixels: [[Option<Rgb888>; 13]; 20],
+    }
+
+    impl Canvas {
+        fn new() -> Self {
+            Self {
+                pixels: [[None; 13]; 20],
+            }
+        }
+    }
+
+    impl DrawTarget for Canvas {
+        type Color = Rgb888;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, color) in pixels {
+                self.pixels[point.y as usize][point.x as usize] = Some(color);
+            }
+            Ok(())
+        }
+    }
+
+    impl OriginDimensions for Canvas {
+        fn size(&self) -> Size {
+            Size::new(13, 20)
+        }
+    }
+
+    fn pattern(x: i32, y: i32) -> TriColor {
+        match (x + 3 * y) % 5 {
+            0 => TriColor::Black,
+            1 | 4 => TriColor::Chromatic,
+            _ => TriColor::White,
+        }
+    }
+
+    #[test]
+    fn mono_preview_matches_pixels() {
+        let mut framebuffer = Framebuffer::<TestSize>::new(DisplayRotation::Rotate90);
+        for point in Rectangle::new(Point::zero(), framebuffer.size()).points() {
+            let black = pattern(point.x, point.y) == TriColor::Black;
+            framebuffer.set_pixel(point.x, point.y, BinaryColor::from(black));
+        }
+
+        let mut canvas = Canvas::new();
+        framebuffer.draw_preview(&mut canvas).unwrap();
+
+        for point in Rectangle::new(Point::zero(), canvas.size()).points() {
+            let expected = match pattern(point.x, point.y) {
+                TriColor::Black => Rgb888::BLACK,
+                _ => Rgb888::WHITE,
+            };
+            assert_eq!(
+                canvas.pixels[point.y as usize][point.x as usize],
+                Some(expected),
+                "{:?}",
+                point
+            );
+        }
+    }
+
+    #[test]
+    fn tricolor_preview_matches_pixels() {
+        let chromatic = Rgb888::new(0xFF, 0xD7, 0x00);
+        let mut framebuffer = TriColorFramebuffer::<TestSize>::new(DisplayRotation::Rotate270);
+        for point in Rectangle::new(Point::zero(), framebuffer.size()).points() {
+            framebuffer.set_pixel(point.x, point.y, pattern(point.x, point.y));
+        }
+
+        let mut canvas = Canvas::new();
+        framebuffer.draw_preview(&mut canvas, chromatic).unwrap();
+
+        for point in Rectangle::new(Point::zero(), canvas.size()).points() {
+            let expected = match pattern(point.x, point.y) {
+                TriColor::Black => Rgb888::BLACK,
+                TriColor::Chromatic => chromatic,
+                TriColor::White => Rgb888::WHITE,
+            };
+            assert_eq!(
+                canvas.pixels[point.y as usize][point.x as usize],
+                Some(expected),
+                "{:?}",
+                point
+            );
+        }
+    }
+}
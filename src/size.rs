@@ -0,0 +1,70 @@
+/// Dimensions of an SSD1680 panel
+pub trait DisplaySize {
+    /// Number of gate lines, the long side of the panel
+    const WIDTH: u16;
+    /// Number of source lines, the short side of the panel
+    const HEIGHT: u16;
+    /// First RAM X address the panel's sources are wired to, in bytes
+    const X_OFFSET: u8;
+    /// Bytes in one gate line, sources rounded up to divisible by 8
+    const STRIDE: usize = (Self::HEIGHT as usize).div_ceil(8);
+
+    /// Buffer holding one RAM plane
+    type Buffer: AsRef<[u8]> + AsMut<[u8]>;
+
+    /// New buffer with every byte set to `fill`
+    fn buffer(fill: u8) -> Self::Buffer;
+}
+
+macro_rules! display_size {
+    ($(#[$meta:meta])* $vis:vis $name:ident, $width:expr, $height:expr, $x_offset:expr) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug)]
+        $vis struct $name;
+
+        impl DisplaySize for $name {
+            const WIDTH: u16 = $width;
+            const HEIGHT: u16 = $height;
+            const X_OFFSET: u8 = $x_offset;
+
+            type Buffer = [u8; usize::div_ceil($height, 8) * $width];
+
+            fn buffer(fill: u8) -> Self::Buffer {
+                [fill; usize::div_ceil($height, 8) * $width]
+            }
+        }
+    };
+}
+
+display_size!(
+    /// 2.13" 250x122 panels, like the Adafruit FeatherWings
+    pub DisplaySize250x122,
+    250,
+    122,
+    1
+);
+
+display_size!(
+    /// 2.9" 296x128 panels
+    pub DisplaySize296x128,
+    296,
+    128,
+    0
+);
+
+display_size!(
+    /// 2.66" 296x152 panels
+    pub DisplaySize296x152,
+    296,
+    152,
+    0
+);
+
+#[cfg(test)]
+display_size!(
+    /// Small panel with a partly used last byte, keeps the test sweeps quick
+    pub(crate) TestSize,
+    20,
+    13,
+    1
+);
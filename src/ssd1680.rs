@@ -1,184 +1,132 @@
 use crate::{
-    command::Command,
-    display::{find_rotation, DisplayRotation},
-    error::Error,
-    interface::SpiInterface,
-    BUF_SIZE, DISPLAY_HEIGHT, DISPLAY_WIDTH,
+    command::MONO_UPDATE_BUSY_MS, framebuffer::Framebuffer, interface::SpiInterface,
+    size::DisplaySize,
 };
 
-use core::convert::Infallible;
-use embedded_hal::digital::v2::OutputPin;
-use embedded_hal_async::delay::DelayUs;
-use embedded_hal_async::digital::Wait;
-use embedded_hal_async::spi::{SpiBus, SpiDevice};
-
 #[cfg(feature = "graphics")]
-use embedded_graphics_core::{
-    draw_target::DrawTarget,
-    geometry::Size,
-    geometry::{Dimensions, OriginDimensions},
-    pixelcolor::BinaryColor,
-    prelude::*,
-};
+use embedded_graphics_core::pixelcolor::BinaryColor;
 
-pub struct Ssd1680<SPI, OPIN, OPIN2, P>
+/// Monochrome driver, used as [`crate::Ssd1680`] on an async bus and
+/// [`crate::blocking::Ssd1680`] on a blocking one
+pub struct Ssd1680<BUS, SIZE>
 where
-    SPI: SpiDevice,
-    SPI::Bus: SpiBus,
-    OPIN: OutputPin<Error = Infallible>,
-    OPIN2: OutputPin<Error = Infallible>,
-    P: Wait<Error = Infallible>,
+    SIZE: DisplaySize,
 {
-    buffer: [u8; BUF_SIZE],
-    display_rotation: DisplayRotation,
-    interface: SpiInterface<SPI, OPIN, OPIN2, P>,
+    pub(crate) framebuffer: Framebuffer<SIZE>,
+    pub(crate) interface: SpiInterface<BUS, SIZE>,
 }
 
-impl<SPI, OPIN, OPIN2, E, P> Ssd1680<SPI, OPIN, OPIN2, P>
-where
-    SPI: SpiDevice<Error = E>,
-    SPI::Bus: SpiBus,
-    OPIN: OutputPin<Error = Infallible>,
-    OPIN2: OutputPin<Error = Infallible>,
-    P: Wait<Error = Infallible>,
-{
-    pub fn new(
-        spi: SPI,
-        dc: OPIN,
-        reset: OPIN2,
-        busy: P,
-        display_rotation: DisplayRotation,
-    ) -> Self {
-        Self {
-            interface: SpiInterface::new(spi, dc, reset, busy),
-            display_rotation,
-            buffer: [0xFF; BUF_SIZE], // inverted
-        }
-    }
+crate::driver::driver!(Ssd1680, Framebuffer, BinaryColor, MONO_UPDATE_BUSY_MS);
+
+/// Flush methods of [`Ssd1680`], expanded for each bus next to
+/// [`driver::sequences`](crate::driver::sequences)
+macro_rules! sequences {
+    (async; $delay:path; $error:ty) => {
+        $crate::ssd1680::sequences!(@write [async] [.await] $delay; $error);
+    };
+    (blocking; $delay:path; $error:ty) => {
+        $crate::ssd1680::sequences!(@write [] [] $delay; $error);
+    };
+
+    (@write [$($async:tt)*] [$($await:tt)*] $delay:path; $error:ty) => {
+        pub $($async)* fn flush<D>(&mut self, delay: &mut D) -> Result<(), $error>
+        where
+            D: $delay,
+        {
+            self.interface.power_up(delay)$($await)*?;
 
-    pub async fn flush<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
-    where
-        D: DelayUs,
-    {
-        self.interface.power_up(delay).await?;
+            self.flush_display(delay)$($await)*?;
 
-        self.interface.set_ram_address(1, 0).await?;
+            self.interface
+                .update($crate::command::UpdateMode::Full, delay)
+                $($await)*?;
 
-        self.interface
-            .write_ram_frame_buffer(&self.buffer, Command::WriteRAM1)
-            .await?;
+            self.interface.power_down()$($await)*
+        }
 
-        self.interface.busy_wait().await?;
-        // update
+        /// Write only the part of the buffer covered by `area` and run a partial
+        /// update, redrawing the changed pixels without flashing the whole panel.
+        #[cfg(feature = "graphics")]
+        pub $($async)* fn flush_partial<D>(
+            &mut self,
+            area: embedded_graphics_core::primitives::Rectangle,
+            delay: &mut D,
+        ) -> Result<(), $error>
+        where
+            D: $delay,
         {
-            self.interface.send_command(Command::DispCtrl2).await?;
-            self.interface.send_data(&[0xF4]).await?;
-
-            self.interface.send_command(Command::MasterActivate).await?;
-            self.interface.busy_wait().await?;
+            use $crate::command::{Command, UpdateMode, WRITE_BUSY_MS};
+
+            let (columns, rows) = match self.framebuffer.window(area) {
+                Some(window) => window,
+                None => return Ok(()),
+            };
+
+            self.interface.power_up(delay)$($await)*?;
+
+            self.interface
+                .set_ram_window(
+                    *columns.start() as u8 + SIZE::X_OFFSET,
+                    *columns.end() as u8 + SIZE::X_OFFSET,
+                    *rows.start() as u16,
+                    *rows.end() as u16,
+                )
+                $($await)*?;
+
+            self.interface
+                .write_ram_area(
+                    self.framebuffer.buffer(),
+                    columns.clone(),
+                    rows.clone(),
+                    Command::WriteRAM1,
+                )
+                $($await)*?;
+            self.interface.busy_wait(delay, WRITE_BUSY_MS)$($await)*?;
+
+            self.interface.update(UpdateMode::Partial, delay)$($await)*?;
+
+            // the partial waveform diffs against RAM2, keep it in sync for the
+            // next update
+            self.interface
+                .write_ram_area(self.framebuffer.buffer(), columns, rows, Command::WriteRAM2)
+                $($await)*?;
+            self.interface.busy_wait(delay, WRITE_BUSY_MS)$($await)*?;
+
+            // later writes of the whole frame expect the full window
+            self.interface.set_full_window()$($await)*?;
+
+            self.interface.power_down()$($await)*
         }
 
-        self.interface.power_down().await
-    }
-
-    pub async fn flush_display(&mut self) -> Result<(), Error<E>> {
-        self.interface.set_ram_address(1, 0).await?;
-
-        self.interface
-            .write_ram_frame_buffer(&self.buffer, Command::WriteRAM1)
-            .await?;
-
-        self.interface.busy_wait().await
-    }
-
-    pub async fn flush_update(&mut self) -> Result<(), Error<E>> {
-        self.interface.send_command(Command::DispCtrl2).await?;
-        self.interface.send_data(&[0xF4]).await?;
-
-        self.interface.send_command(Command::MasterActivate).await?;
-        self.interface.busy_wait().await
-    }
-
-    pub async fn power_down(&mut self) -> Result<(), Error<E>> {
-        self.interface.power_down().await
-    }
-
-    pub async fn power_up<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
-    where
-        D: DelayUs,
-    {
-        self.interface.power_up(delay).await
-    }
-
-    pub fn set_pixel(&mut self, x: u32, y: u32, color: BinaryColor) {
-        let height = ((DISPLAY_HEIGHT as usize + 7) / 8) as u32;
-
-        let (nx, ny) = find_rotation(
-            x,
-            y,
-            DISPLAY_HEIGHT.into(),
-            DISPLAY_WIDTH.into(),
-            self.display_rotation,
-        );
-
-        let (index, bit) = (
-            ny / 8 + height * (DISPLAY_WIDTH as u32 - 1 - nx),
-            0x80 >> (ny % 8),
-        );
-
-        let index = index as usize;
-        if index >= self.buffer.len() {
-            return;
-        }
+        pub $($async)* fn flush_display<D>(&mut self, delay: &mut D) -> Result<(), $error>
+        where
+            D: $delay,
+        {
+            use $crate::command::{Command, WRITE_BUSY_MS};
 
-        match color {
-            BinaryColor::On => {
-                self.buffer[index] &= !bit;
-            }
-            BinaryColor::Off => {
-                self.buffer[index] |= bit;
-            }
-        }
-    }
-}
+            self.interface.wake(delay)$($await)*?;
+            self.interface.wait_update_complete(delay)$($await)*?;
 
-#[cfg(feature = "graphics")]
-impl<SPI, OPIN, OPIN2, E, P> DrawTarget for Ssd1680<SPI, OPIN, OPIN2, P>
-where
-    SPI: SpiDevice<Error = E>,
-    SPI::Bus: SpiBus,
-    OPIN: OutputPin<Error = Infallible>,
-    OPIN2: OutputPin<Error = Infallible>,
-    P: Wait<Error = Infallible>,
-{
-    type Color = BinaryColor;
-    type Error = core::convert::Infallible;
-
-    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
-    where
-        I: IntoIterator<Item = Pixel<Self::Color>>,
-    {
-        let bb = self.bounding_box();
-
-        pixels
-            .into_iter()
-            .filter(|Pixel(pos, _color)| bb.contains(*pos))
-            .for_each(|Pixel(pos, color)| self.set_pixel(pos.x as u32, pos.y as u32, color));
-
-        Ok(())
-    }
-}
+            // without a reset pin the controller keeps the window it had
+            self.interface.set_full_window()$($await)*?;
+            self.interface.set_ram_address(SIZE::X_OFFSET, 0)$($await)*?;
 
-#[cfg(feature = "graphics")]
-impl<SPI, OPIN, OPIN2, E, P> OriginDimensions for Ssd1680<SPI, OPIN, OPIN2, P>
-where
-    SPI: SpiDevice<Error = E>,
-    SPI::Bus: SpiBus,
-    OPIN: OutputPin<Error = Infallible>,
-    OPIN2: OutputPin<Error = Infallible>,
-    P: Wait<Error = Infallible>,
-{
-    fn size(&self) -> Size {
-        Size::new(DISPLAY_WIDTH.into(), DISPLAY_HEIGHT.into())
-    }
+            self.interface
+                .write_ram_frame_buffer(self.framebuffer.buffer(), Command::WriteRAM1)
+                $($await)*?;
+
+            self.interface.busy_wait(delay, WRITE_BUSY_MS)$($await)*?;
+
+            // RAM2 holds the previous image a partial update diffs against
+            self.interface.set_ram_address(SIZE::X_OFFSET, 0)$($await)*?;
+
+            self.interface
+                .write_ram_frame_buffer(self.framebuffer.buffer(), Command::WriteRAM2)
+                $($await)*?;
+
+            self.interface.busy_wait(delay, WRITE_BUSY_MS)$($await)*
+        }
+    };
 }
+
+pub(crate) use sequences;
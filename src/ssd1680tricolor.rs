@@ -1,205 +1,101 @@
 use crate::{
-    command::Command,
-    display::{find_rotation, DisplayRotation},
-    error::Error,
+    color::TriColor,
+    command::TRICOLOR_UPDATE_BUSY_MS,
+    framebuffer::{Layer, TriColorFramebuffer},
     interface::SpiInterface,
-    BUF_SIZE, DISPLAY_HEIGHT, DISPLAY_WIDTH,
+    size::DisplaySize,
 };
 
-use core::convert::Infallible;
-use embedded_hal::digital::v2::OutputPin;
-use embedded_hal_async::delay::DelayUs;
-use embedded_hal_async::digital::Wait;
-use embedded_hal_async::spi::{SpiBus, SpiDevice};
-
-#[cfg(feature = "graphics")]
-use embedded_graphics_core::{
-    draw_target::DrawTarget,
-    geometry::Size,
-    geometry::{Dimensions, OriginDimensions},
-    prelude::*,
-};
-
-pub struct Ssd1680TriColor<SPI, OPIN, OPIN2, P>
+/// Black, white and red or yellow driver, used as [`crate::Ssd1680TriColor`]
+/// on an async bus and [`crate::blocking::Ssd1680TriColor`] on a blocking one
+pub struct Ssd1680TriColor<BUS, SIZE>
 where
-    SPI: SpiDevice,
-    SPI::Bus: SpiBus,
-    OPIN: OutputPin<Error = Infallible>,
-    OPIN2: OutputPin<Error = Infallible>,
-    P: Wait<Error = Infallible>,
+    SIZE: DisplaySize,
 {
-    buffer: [u8; BUF_SIZE],
-    buffer2: [u8; BUF_SIZE],
-    display_rotation: DisplayRotation,
-    interface: SpiInterface<SPI, OPIN, OPIN2, P>,
+    pub(crate) framebuffer: TriColorFramebuffer<SIZE>,
+    pub(crate) interface: SpiInterface<BUS, SIZE>,
 }
 
-impl<SPI, OPIN, OPIN2, E, P> Ssd1680TriColor<SPI, OPIN, OPIN2, P>
+crate::driver::driver!(
+    Ssd1680TriColor,
+    TriColorFramebuffer,
+    TriColor,
+    TRICOLOR_UPDATE_BUSY_MS
+);
+
+impl<BUS, SIZE> Ssd1680TriColor<BUS, SIZE>
 where
-    SPI: SpiDevice<Error = E>,
-    SPI::Bus: SpiBus,
-    OPIN: OutputPin<Error = Infallible>,
-    OPIN2: OutputPin<Error = Infallible>,
-    P: Wait<Error = Infallible>,
+    SIZE: DisplaySize,
 {
-    pub fn new(
-        spi: SPI,
-        dc: OPIN,
-        reset: OPIN2,
-        busy: P,
-        display_rotation: DisplayRotation,
-    ) -> Self {
-        Self {
-            interface: SpiInterface::new(spi, dc, reset, busy),
-            display_rotation,
-            buffer: [0xFF; BUF_SIZE], // inverted
-            buffer2: [0x00; BUF_SIZE],
-        }
+    /// Black plane as a monochrome draw target, leaving chromatic pixels as
+    /// they are, see [`TriColorFramebuffer::black_layer`]
+    pub fn black_layer(&mut self) -> Layer<'_, SIZE> {
+        self.framebuffer.black_layer()
     }
 
-    pub async fn flush<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
-    where
-        D: DelayUs,
-    {
-        self.interface.power_up(delay).await?;
-
-        self.interface.set_ram_address(1, 0).await?;
-
-        self.interface
-            .write_ram_frame_buffer(&self.buffer, Command::WriteRAM1)
-            .await?;
-
-        self.interface.busy_wait().await?;
-
-        self.interface.set_ram_address(1, 0).await?;
-
-        self.interface
-            .write_ram_frame_buffer(&self.buffer2, Command::WriteRAM2)
-            .await?;
-
-        self.interface.busy_wait().await?;
+    /// Chromatic plane as a monochrome draw target, for highlights drawn over
+    /// the black plane, see [`TriColorFramebuffer::chromatic_layer`]
+    pub fn chromatic_layer(&mut self) -> Layer<'_, SIZE> {
+        self.framebuffer.chromatic_layer()
+    }
+}
 
-        // update
+/// Flush methods of [`Ssd1680TriColor`], expanded for each bus next to
+/// [`driver::sequences`](crate::driver::sequences)
+macro_rules! sequences {
+    (async; $delay:path; $error:ty) => {
+        $crate::ssd1680tricolor::sequences!(@write [async] [.await] $delay; $error);
+    };
+    (blocking; $delay:path; $error:ty) => {
+        $crate::ssd1680tricolor::sequences!(@write [] [] $delay; $error);
+    };
+
+    (@write [$($async:tt)*] [$($await:tt)*] $delay:path; $error:ty) => {
+        pub $($async)* fn flush<D>(&mut self, delay: &mut D) -> Result<(), $error>
+        where
+            D: $delay,
         {
-            self.interface.send_command(Command::DispCtrl2).await?;
-            self.interface.send_data(&[0xF4]).await?;
+            self.interface.power_up(delay)$($await)*?;
 
-            self.interface.send_command(Command::MasterActivate).await?;
-            self.interface.busy_wait().await?;
-        }
-
-        self.interface.power_down().await
-    }
+            self.flush_display(delay)$($await)*?;
 
-    pub fn set_pixel(&mut self, x: u32, y: u32, color: TriColor) {
-        let height = ((DISPLAY_HEIGHT as usize + 7) / 8) as u32;
-
-        let (nx, ny) = find_rotation(
-            x,
-            y,
-            DISPLAY_HEIGHT.into(),
-            DISPLAY_WIDTH.into(),
-            self.display_rotation,
-        );
-
-        let (index, bit) = (
-            nx / 8 + height * (DISPLAY_WIDTH as u32 - 1 - ny),
-            0x80 >> (nx % 8),
-        );
-
-        let index = index as usize;
-        if index >= self.buffer.len() {
-            return;
-        }
+            self.interface
+                .update($crate::command::UpdateMode::Full, delay)
+                $($await)*?;
 
-        match color {
-            TriColor::Black => {
-                self.buffer[index] &= !bit;
-                self.buffer2[index] &= !bit;
-            }
-            TriColor::Chromatic => {
-                self.buffer[index] |= bit;
-                self.buffer2[index] |= bit;
-            }
-            TriColor::White => {
-                self.buffer[index] |= bit;
-                self.buffer2[index] &= !bit;
-            }
+            self.interface.power_down()$($await)*
         }
-    }
-}
 
-#[cfg(feature = "graphics")]
-impl<SPI, OPIN, OPIN2, E, P> DrawTarget for Ssd1680TriColor<SPI, OPIN, OPIN2, P>
-where
-    SPI: SpiDevice<Error = E>,
-    SPI::Bus: SpiBus,
-    OPIN: OutputPin<Error = Infallible>,
-    OPIN2: OutputPin<Error = Infallible>,
-    P: Wait<Error = Infallible>,
-{
-    type Color = TriColor;
-    type Error = core::convert::Infallible;
+        /// Write the black/white plane to RAM1 and the chromatic plane to RAM2,
+        /// waking the controller up when it is not awake
+        pub $($async)* fn flush_display<D>(&mut self, delay: &mut D) -> Result<(), $error>
+        where
+            D: $delay,
+        {
+            use $crate::command::{Command, WRITE_BUSY_MS};
 
-    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
-    where
-        I: IntoIterator<Item = Pixel<Self::Color>>,
-    {
-        let bb = self.bounding_box();
+            self.interface.wake(delay)$($await)*?;
+            self.interface.wait_update_complete(delay)$($await)*?;
 
-        pixels
-            .into_iter()
-            .filter(|Pixel(pos, _color)| bb.contains(*pos))
-            .for_each(|Pixel(pos, color)| self.set_pixel(pos.x as u32, pos.y as u32, color));
+            // without a reset pin the controller keeps the window it had
+            self.interface.set_full_window()$($await)*?;
+            self.interface.set_ram_address(SIZE::X_OFFSET, 0)$($await)*?;
 
-        Ok(())
-    }
-}
+            self.interface
+                .write_ram_frame_buffer(self.framebuffer.buffer(), Command::WriteRAM1)
+                $($await)*?;
 
-#[cfg(feature = "graphics")]
-impl<SPI, OPIN, OPIN2, E, P> OriginDimensions for Ssd1680TriColor<SPI, OPIN, OPIN2, P>
-where
-    SPI: SpiDevice<Error = E>,
-    SPI::Bus: SpiBus,
-    OPIN: OutputPin<Error = Infallible>,
-    OPIN2: OutputPin<Error = Infallible>,
-    P: Wait<Error = Infallible>,
-{
-    fn size(&self) -> Size {
-        Size::new(DISPLAY_WIDTH.into(), DISPLAY_HEIGHT.into())
-    }
-}
+            self.interface.busy_wait(delay, WRITE_BUSY_MS)$($await)*?;
 
-/// Only for the Black/White/Color-Displays
-#[derive(Clone, Copy, PartialEq, Debug)]
-pub enum TriColor {
-    /// Black color
-    Black,
-    /// White color
-    White,
-    /// Chromatic color
-    Chromatic,
-}
+            self.interface.set_ram_address(SIZE::X_OFFSET, 0)$($await)*?;
 
-impl TriColor {
-    /// Get the color encoding of the color for one bit
-    pub fn get_bit_value(self) -> u8 {
-        match self {
-            TriColor::White => 1u8,
-            TriColor::Black | TriColor::Chromatic => 0u8,
-        }
-    }
+            self.interface
+                .write_ram_frame_buffer(self.framebuffer.buffer2(), Command::WriteRAM2)
+                $($await)*?;
 
-    /// Gets a full byte of black or white pixels
-    pub fn get_byte_value(self) -> u8 {
-        match self {
-            TriColor::White => 0xff,
-            TriColor::Black | TriColor::Chromatic => 0x00,
+            self.interface.busy_wait(delay, WRITE_BUSY_MS)$($await)*
         }
-    }
+    };
 }
 
-#[cfg(feature = "graphics")]
-impl PixelColor for TriColor {
-    type Raw = ();
-}
+pub(crate) use sequences;
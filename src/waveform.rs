@@ -0,0 +1,62 @@
+use crate::command::Command;
+
+/// Size of the SSD1680 waveform lookup table written with `0x32`
+pub const LUT_SIZE: usize = 153;
+
+/// Custom waveform uploaded to the controller in place of the one in OTP
+#[derive(Clone, Copy, Debug)]
+pub struct Waveform {
+    /// Voltage selection, phase timing and frame rate table
+    pub lut: [u8; LUT_SIZE],
+    /// Option for LUT end (`0x3F`)
+    pub end_option: u8,
+    /// Gate driving voltage VGH (`0x03`)
+    pub gate_voltage: u8,
+    /// Source driving voltages VSH1, VSH2 and VSL (`0x04`)
+    pub source_voltage: [u8; 3],
+    /// VCOM register (`0x2C`)
+    pub vcom: u8,
+}
+
+impl Waveform {
+    /// Waveform from the 159 byte layout used by panel vendors sample code,
+    /// the table followed by end option, gate voltage, the three source
+    /// voltages and VCOM
+    pub const fn from_bytes(bytes: &[u8; LUT_SIZE + 6]) -> Self {
+        let mut lut = [0; LUT_SIZE];
+        let mut i = 0;
+        while i < LUT_SIZE {
+            lut[i] = bytes[i];
+            i += 1;
+        }
+
+        Self {
+            lut,
+            end_option: bytes[LUT_SIZE],
+            gate_voltage: bytes[LUT_SIZE + 1],
+            source_voltage: [
+                bytes[LUT_SIZE + 2],
+                bytes[LUT_SIZE + 3],
+                bytes[LUT_SIZE + 4],
+            ],
+            vcom: bytes[LUT_SIZE + 5],
+        }
+    }
+
+    /// Commands uploading the waveform
+    pub(crate) fn commands(&self) -> [(Command, &[u8]); 5] {
+        [
+            (Command::WriteLut, &self.lut),
+            (
+                Command::LutEndOption,
+                core::slice::from_ref(&self.end_option),
+            ),
+            (
+                Command::GateVoltage,
+                core::slice::from_ref(&self.gate_voltage),
+            ),
+            (Command::SourceVoltage, &self.source_voltage),
+            (Command::Vcom, core::slice::from_ref(&self.vcom)),
+        ]
+    }
+}
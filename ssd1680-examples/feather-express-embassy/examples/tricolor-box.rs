@@ -22,9 +22,9 @@
 //! P0_13 mosi
 //! P0_15 miso
 //! skip 3
-//! P0_30 rst MUST SOLDER
+//! P0_30 rst solder, or pass None
 //!
-//! P0_06 11 busy MUST SOLDER
+//! P0_06 11 busy solder, or pass None
 //! P0_27 10 dc
 //! P0_26 9 cs
 //! P0_07 6 srcs
@@ -52,7 +52,7 @@ use embedded_graphics::{
 };
 use embedded_hal_async::delay::DelayUs;
 use embedded_hal_async::spi::ExclusiveDevice;
-use ssd1680::{DisplayRotation, Ssd1680TriColor, TriColor};
+use ssd1680::{DisplayRotation, DisplaySize250x122, Ssd1680TriColor, TriColor};
 
 // we make a lazily created static
 static EXECUTOR: Forever<embassy::executor::Executor> = Forever::new();
@@ -134,7 +134,14 @@ pub async fn display_task() {
 
     let busy = gpio::Input::new(dp.P0_06.degrade(), gpio::Pull::Up);
 
-    let mut ssd1680 = Ssd1680TriColor::new(spi_dev, dc, reset, busy, DisplayRotation::Rotate0);
+    let mut ssd1680 = Ssd1680TriColor::new(
+        spi_dev,
+        dc,
+        Some(reset),
+        Some(busy),
+        DisplaySize250x122,
+        DisplayRotation::Rotate0,
+    );
 
     Rectangle::new(Point::new(0, 0), Size::new(15, 15))
         .into_styled(PrimitiveStyle::with_fill(TriColor::Chromatic))
@@ -0,0 +1,89 @@
+use embedded_graphics_core::pixelcolor::BinaryColor;
+use ssd1680::{
+    blocking,
+    mock::{block_on, Event, Mock, MockBusy, MockDc, MockReset, MockSpi},
+    DisplayRotation, DisplaySize250x122, Error, Ssd1680,
+};
+
+type Display = Ssd1680<MockSpi, MockDc, MockReset, MockBusy, DisplaySize250x122>;
+
+fn display(mock: &Mock) -> Display {
+    Ssd1680::new(
+        mock.spi(),
+        mock.dc(),
+        Some(mock.reset()),
+        Some(mock.busy()),
+        DisplaySize250x122,
+        DisplayRotation::Rotate0,
+    )
+}
+
+/// Display powered up, with the power up left out of the log
+fn awake_display(mock: &Mock) -> Display {
+    let mut display = display(mock);
+    block_on(display.power_up(&mut mock.delay())).unwrap();
+    mock.clear();
+    display
+}
+
+#[test]
+fn flush_sends_the_same_commands_as_blocking() {
+    let mock = Mock::new();
+    let mut display = display(&mock);
+    display.set_pixel(3, 7, BinaryColor::On);
+    block_on(display.flush(&mut mock.delay())).unwrap();
+
+    let blocking_mock = Mock::new();
+    let mut blocking_display = blocking::Ssd1680::new(
+        blocking_mock.spi(),
+        blocking_mock.dc(),
+        Some(blocking_mock.reset()),
+        Some(blocking_mock.busy()),
+        DisplaySize250x122,
+        DisplayRotation::Rotate0,
+    );
+    blocking_display.set_pixel(3, 7, BinaryColor::On);
+    blocking_display.flush(&mut blocking_mock.delay()).unwrap();
+
+    assert_eq!(
+        mock.transactions().unwrap(),
+        blocking_mock.transactions().unwrap()
+    );
+}
+
+#[test]
+fn busy_wait_returns_once_busy_goes_low() {
+    let mock = Mock::new();
+    let mut display = awake_display(&mock);
+    display.set_busy_timeout(5);
+    mock.set_busy_polls(3);
+
+    block_on(display.flush_update(&mut mock.delay())).unwrap();
+
+    assert!(!display.is_updating());
+}
+
+#[test]
+fn busy_wait_times_out() {
+    let mock = Mock::new();
+    let mut display = awake_display(&mock);
+    display.set_busy_timeout(5);
+    mock.set_busy_polls(100);
+
+    let result = block_on(display.flush_update(&mut mock.delay()));
+
+    assert!(matches!(result, Err(Error::BusyTimeout)));
+    assert!(mock.events().contains(&Event::Delay(5)));
+}
+
+#[test]
+fn read_temperature_reads_the_sensor() {
+    let mock = Mock::new();
+    let mut display = awake_display(&mock);
+
+    mock.queue_read(&[0xE6, 0x80]);
+    let celsius = block_on(display.read_temperature(&mut mock.delay())).unwrap();
+
+    assert_eq!(celsius, -25.5);
+    assert!(mock.events().contains(&Event::Read(vec![0xE6, 0x80])));
+}
@@ -0,0 +1,265 @@
+use embedded_graphics_core::{pixelcolor::BinaryColor, prelude::*, primitives::Rectangle};
+use ssd1680::{
+    blocking::{Ssd1680, Ssd1680TriColor},
+    mock::{Emulator, Event, Mock, MockBusy, MockDc, MockReset, MockSpi},
+    DisplayRotation, DisplaySize, DisplaySize250x122, NoPin, PowerState, TriColor,
+};
+
+type Display = Ssd1680<MockSpi, MockDc, MockReset, MockBusy, DisplaySize250x122>;
+type TriColorDisplay = Ssd1680TriColor<MockSpi, MockDc, MockReset, MockBusy, DisplaySize250x122>;
+
+const ROTATIONS: [DisplayRotation; 4] = [
+    DisplayRotation::Rotate0,
+    DisplayRotation::Rotate90,
+    DisplayRotation::Rotate180,
+    DisplayRotation::Rotate270,
+];
+
+fn display(mock: &Mock, rotation: DisplayRotation) -> Display {
+    Ssd1680::new(
+        mock.spi(),
+        mock.dc(),
+        Some(mock.reset()),
+        Some(mock.busy()),
+        DisplaySize250x122,
+        rotation,
+    )
+}
+
+fn tricolor_display(mock: &Mock, rotation: DisplayRotation) -> TriColorDisplay {
+    Ssd1680TriColor::new(
+        mock.spi(),
+        mock.dc(),
+        Some(mock.reset()),
+        Some(mock.busy()),
+        DisplaySize250x122,
+        rotation,
+    )
+}
+
+/// Gate and source showing the logical pixel
+fn physical(rotation: DisplayRotation, x: i32, y: i32) -> (u16, u16) {
+    rotation
+        .panel_position(x, y, DisplaySize250x122::WIDTH, DisplaySize250x122::HEIGHT)
+        .expect("pixel on the panel")
+}
+
+fn logical_pixels(size: Size) -> impl Iterator<Item = (i32, i32)> {
+    let (width, height) = (size.width as i32, size.height as i32);
+    (0..height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+}
+
+/// Asymmetric pattern, a mirrored or transposed image doesn't match it
+fn mono_pattern(x: i32, y: i32) -> BinaryColor {
+    if x == 2 * y || (x + 3 * y) % 7 == 0 {
+        BinaryColor::On
+    } else {
+        BinaryColor::Off
+    }
+}
+
+fn tricolor_pattern(x: i32, y: i32) -> TriColor {
+    match (x + 2 * y) % 5 {
+        0 => TriColor::Black,
+        1 | 3 => TriColor::Chromatic,
+        _ => TriColor::White,
+    }
+}
+
+#[test]
+fn mono_flush_shows_drawn_pixels() {
+    for rotation in ROTATIONS {
+        let mock = Mock::new();
+        let mut display = display(&mock, rotation);
+
+        let size = display.size();
+        for (x, y) in logical_pixels(size) {
+            display.set_pixel(x, y, mono_pattern(x, y));
+        }
+        display.flush(&mut mock.delay()).unwrap();
+
+        let mut emulator = Emulator::<DisplaySize250x122>::new();
+        emulator.feed(&mock.events());
+
+        assert_eq!(emulator.updates(), 1);
+        assert!(emulator.is_asleep());
+        for (x, y) in logical_pixels(size) {
+            let (gate, source) = physical(rotation, x, y);
+            assert_eq!(
+                emulator.displayed(gate, source),
+                mono_pattern(x, y),
+                "{:?} ({}, {})",
+                rotation,
+                x,
+                y
+            );
+        }
+    }
+}
+
+#[test]
+fn mono_partial_flush_updates_area() {
+    for rotation in ROTATIONS {
+        let mock = Mock::new();
+        let mut display = display(&mock, rotation);
+        let mut emulator = Emulator::<DisplaySize250x122>::new();
+
+        display.flush(&mut mock.delay()).unwrap();
+        emulator.feed(&mock.events());
+        mock.clear();
+
+        let area = Rectangle::new(Point::new(13, 21), Size::new(30, 17));
+        display.fill_solid(&area, BinaryColor::On).unwrap();
+        display.flush_partial(area, &mut mock.delay()).unwrap();
+        emulator.feed(&mock.events());
+
+        assert_eq!(emulator.updates(), 2);
+        for (x, y) in logical_pixels(display.size()) {
+            let (gate, source) = physical(rotation, x, y);
+            let expected = if area.contains(Point::new(x, y)) {
+                BinaryColor::On
+            } else {
+                BinaryColor::Off
+            };
+            assert_eq!(
+                emulator.displayed(gate, source),
+                expected,
+                "{:?} ({}, {})",
+                rotation,
+                x,
+                y
+            );
+
+            // the base image of the next partial update
+            let (ram1, ram2) = emulator.ram(gate, source);
+            assert_eq!(ram1, ram2, "{:?} ({}, {})", rotation, x, y);
+        }
+    }
+}
+
+#[test]
+fn tricolor_flush_shows_drawn_pixels() {
+    for rotation in ROTATIONS {
+        let mock = Mock::new();
+        let mut display = tricolor_display(&mock, rotation);
+
+        let size = display.size();
+        for (x, y) in logical_pixels(size) {
+            display.set_pixel(x, y, tricolor_pattern(x, y));
+        }
+        display.flush(&mut mock.delay()).unwrap();
+
+        let mut emulator = Emulator::<DisplaySize250x122>::new();
+        emulator.feed(&mock.events());
+
+        assert_eq!(emulator.updates(), 1);
+        for (x, y) in logical_pixels(size) {
+            let (gate, source) = physical(rotation, x, y);
+            assert_eq!(
+                emulator.displayed_tricolor(gate, source),
+                tricolor_pattern(x, y),
+                "{:?} ({}, {})",
+                rotation,
+                x,
+                y
+            );
+        }
+    }
+}
+
+#[test]
+fn writes_after_deep_sleep_wake_the_controller() {
+    let mock = Mock::new();
+    let mut display = display(&mock, DisplayRotation::Rotate0);
+    let mut emulator = Emulator::<DisplaySize250x122>::new();
+
+    display.flush(&mut mock.delay()).unwrap();
+    emulator.feed(&mock.events());
+    mock.clear();
+    assert_eq!(display.power_state(), PowerState::DeepSleep);
+
+    // without calling power_up
+    display.set_pixel(0, 0, BinaryColor::On);
+    display.flush_display(&mut mock.delay()).unwrap();
+    display.flush_update(&mut mock.delay()).unwrap();
+    emulator.feed(&mock.events());
+
+    assert_eq!(mock.events()[0], Event::Reset(false));
+    assert_eq!(display.power_state(), PowerState::Awake);
+    assert_eq!(emulator.updates(), 2);
+    assert_eq!(emulator.displayed(249, 0), BinaryColor::On);
+}
+
+#[test]
+fn staged_flush_after_partial_flush_without_reset_pin() {
+    let mock = Mock::new();
+    let mut display = Ssd1680::new(
+        mock.spi(),
+        mock.dc(),
+        None::<NoPin>,
+        Some(mock.busy()),
+        DisplaySize250x122,
+        DisplayRotation::Rotate0,
+    );
+    let mut emulator = Emulator::<DisplaySize250x122>::new();
+
+    display.flush(&mut mock.delay()).unwrap();
+    let area = Rectangle::new(Point::new(10, 10), Size::new(8, 8));
+    display.flush_partial(area, &mut mock.delay()).unwrap();
+    assert_eq!(display.power_state(), PowerState::Awake);
+
+    display.set_pixel(200, 100, BinaryColor::On);
+    display.flush_display(&mut mock.delay()).unwrap();
+    display.flush_update(&mut mock.delay()).unwrap();
+    emulator.feed(&mock.events());
+
+    let (gate, source) = physical(DisplayRotation::Rotate0, 200, 100);
+    assert_eq!((gate, source), (49, 100));
+    assert_eq!(emulator.updates(), 3);
+    assert_eq!(emulator.displayed(gate, source), BinaryColor::On);
+}
+
+#[test]
+fn tricolor_stays_awake_across_staged_updates() {
+    let mock = Mock::new();
+    let mut display = tricolor_display(&mock, DisplayRotation::Rotate0);
+    let mut emulator = Emulator::<DisplaySize250x122>::new();
+
+    display.power_up(&mut mock.delay()).unwrap();
+    display.set_pixel(0, 0, TriColor::Black);
+    display.flush_display(&mut mock.delay()).unwrap();
+    display.flush_update(&mut mock.delay()).unwrap();
+
+    display.set_pixel(0, 0, TriColor::Chromatic);
+    display.flush_display(&mut mock.delay()).unwrap();
+    display.flush_update(&mut mock.delay()).unwrap();
+    emulator.feed(&mock.events());
+
+    assert_eq!(emulator.updates(), 2);
+    assert!(!emulator.is_asleep());
+    assert_eq!(emulator.displayed_tricolor(249, 0), TriColor::Chromatic);
+    assert_eq!(emulator.displayed_tricolor(249, 1), TriColor::White);
+
+    mock.clear();
+    display.power_down().unwrap();
+    emulator.feed(&mock.events());
+    assert!(emulator.is_asleep());
+}
+
+#[test]
+fn rotation_change_while_awake_reaches_the_controller() {
+    let mock = Mock::new();
+    let mut display = display(&mock, DisplayRotation::Rotate0);
+    let mut emulator = Emulator::<DisplaySize250x122>::new();
+
+    display.power_up(&mut mock.delay()).unwrap();
+    display.set_rotation(DisplayRotation::Rotate180);
+    display.set_pixel(5, 0, BinaryColor::On);
+    display.flush_display(&mut mock.delay()).unwrap();
+    display.flush_update(&mut mock.delay()).unwrap();
+    emulator.feed(&mock.events());
+
+    let (gate, source) = physical(DisplayRotation::Rotate180, 5, 0);
+    assert_eq!((gate, source), (5, 121));
+    assert_eq!(emulator.displayed(gate, source), BinaryColor::On);
+}
@@ -0,0 +1,474 @@
+use embedded_graphics_core::{prelude::*, primitives::Rectangle};
+use embedded_hal::digital::v2::OutputPin;
+use ssd1680::{
+    blocking::Ssd1680,
+    mock::{Event, Mock, MockBusy, MockDc, MockReset, MockSpi, Transaction},
+    waveform::LUT_SIZE,
+    Command, DisplayRotation, DisplaySize250x122, Error, NoPin, PinError, PowerState, Waveform,
+};
+
+type Display = Ssd1680<MockSpi, MockDc, MockReset, MockBusy, DisplaySize250x122>;
+
+fn display(mock: &Mock) -> Display {
+    Ssd1680::new(
+        mock.spi(),
+        mock.dc(),
+        Some(mock.reset()),
+        Some(mock.busy()),
+        DisplaySize250x122,
+        DisplayRotation::Rotate0,
+    )
+}
+
+/// Display powered up, with the power up left out of the log
+fn awake_display(mock: &Mock) -> Display {
+    let mut display = display(mock);
+    display.power_up(&mut mock.delay()).unwrap();
+    mock.clear();
+    display
+}
+
+fn transaction(command: Command, data: &[u8]) -> Transaction {
+    Transaction {
+        command,
+        data: data.to_vec(),
+    }
+}
+
+fn commands(mock: &Mock) -> Vec<Command> {
+    mock.transactions()
+        .unwrap()
+        .into_iter()
+        .map(|transaction| transaction.command)
+        .collect()
+}
+
+/// Rotate0 decrements Y, the first row goes to the last gate, 249
+fn power_up_sequence() -> Vec<Transaction> {
+    vec![
+        transaction(Command::Reset, &[]),
+        transaction(Command::DataMode, &[0x01]),
+        transaction(Command::Border, &[0x05]),
+        transaction(Command::Vcom, &[0x36]),
+        transaction(Command::GateVoltage, &[0x17]),
+        transaction(Command::SourceVoltage, &[0x41, 0x00, 0x32]),
+        transaction(Command::TempSensor, &[0x80]),
+        transaction(Command::RamXCount, &[0x01]),
+        transaction(Command::RamYCount, &[249, 0]),
+        transaction(Command::RamXPos, &[0x01, 0x10]),
+        transaction(Command::RamYPos, &[249, 0, 0, 0]),
+        transaction(Command::Control, &[249, 0, 0]),
+    ]
+}
+
+#[test]
+fn power_up_resets_and_initialises() {
+    let mock = Mock::new();
+    let mut display = display(&mock);
+
+    display.power_up(&mut mock.delay()).unwrap();
+
+    assert_eq!(
+        mock.events()[..3],
+        [Event::Reset(false), Event::Delay(10), Event::Reset(true)]
+    );
+    assert_eq!(mock.transactions().unwrap(), power_up_sequence());
+}
+
+#[test]
+fn flush_display_writes_both_rams() {
+    let mock = Mock::new();
+    let mut display = awake_display(&mock);
+
+    display.flush_display(&mut mock.delay()).unwrap();
+
+    let buffer = display.framebuffer().buffer();
+    assert_eq!(buffer.len(), 16 * 250);
+    assert_eq!(
+        mock.transactions().unwrap(),
+        [
+            transaction(Command::RamXPos, &[0x01, 0x10]),
+            transaction(Command::RamYPos, &[249, 0, 0, 0]),
+            transaction(Command::RamXCount, &[0x01]),
+            transaction(Command::RamYCount, &[249, 0]),
+            transaction(Command::WriteRAM1, buffer),
+            transaction(Command::RamXCount, &[0x01]),
+            transaction(Command::RamYCount, &[249, 0]),
+            transaction(Command::WriteRAM2, buffer),
+        ]
+    );
+}
+
+#[test]
+fn flush_update_runs_full_update() {
+    let mock = Mock::new();
+    let mut display = awake_display(&mock);
+
+    display.flush_update(&mut mock.delay()).unwrap();
+
+    assert_eq!(
+        mock.transactions().unwrap(),
+        [
+            transaction(Command::DispCtrl2, &[0xF4]),
+            transaction(Command::MasterActivate, &[]),
+        ]
+    );
+}
+
+#[test]
+fn custom_waveform_is_uploaded_and_skips_otp_load() {
+    let mock = Mock::new();
+    let mut display = display(&mock);
+    let mut bytes = [0x11; LUT_SIZE + 6];
+    bytes[LUT_SIZE..].copy_from_slice(&[0x22, 0x17, 0x41, 0xA8, 0x32, 0x50]);
+    display.set_waveform(Some(Waveform::from_bytes(&bytes)));
+
+    display.power_up(&mut mock.delay()).unwrap();
+
+    let transactions = mock.transactions().unwrap();
+    let power_up = power_up_sequence();
+    assert_eq!(transactions[..power_up.len()], power_up);
+    assert_eq!(
+        transactions[power_up.len()..],
+        [
+            transaction(Command::WriteLut, &[0x11; LUT_SIZE]),
+            transaction(Command::LutEndOption, &[0x22]),
+            transaction(Command::GateVoltage, &[0x17]),
+            transaction(Command::SourceVoltage, &[0x41, 0xA8, 0x32]),
+            transaction(Command::Vcom, &[0x50]),
+        ]
+    );
+
+    mock.clear();
+    display.flush_update(&mut mock.delay()).unwrap();
+    assert_eq!(
+        mock.transactions().unwrap()[0],
+        transaction(Command::DispCtrl2, &[0xC4])
+    );
+
+    mock.clear();
+    let area = Rectangle::new(Point::new(10, 10), Size::new(8, 8));
+    display.flush_partial(area, &mut mock.delay()).unwrap();
+    assert!(mock
+        .transactions()
+        .unwrap()
+        .contains(&transaction(Command::DispCtrl2, &[0xCC])));
+}
+
+#[test]
+fn waveform_set_while_awake_is_uploaded_before_update() {
+    let mock = Mock::new();
+    let mut display = awake_display(&mock);
+    let waveform = Waveform::from_bytes(&[0x11; LUT_SIZE + 6]);
+
+    display.set_waveform(Some(waveform));
+    display.flush_display(&mut mock.delay()).unwrap();
+    display.flush_update(&mut mock.delay()).unwrap();
+
+    let transactions = mock.transactions().unwrap();
+    assert_eq!(
+        commands(&mock)[..5],
+        [
+            Command::WriteLut,
+            Command::LutEndOption,
+            Command::GateVoltage,
+            Command::SourceVoltage,
+            Command::Vcom,
+        ]
+    );
+    assert_eq!(
+        transactions[transactions.len() - 2..],
+        [
+            transaction(Command::DispCtrl2, &[0xC4]),
+            transaction(Command::MasterActivate, &[]),
+        ]
+    );
+
+    // uploaded once, the LUT register keeps it
+    mock.clear();
+    display.flush_update(&mut mock.delay()).unwrap();
+    assert!(!commands(&mock).contains(&Command::WriteLut));
+}
+
+#[test]
+fn host_temperature_is_written_before_update() {
+    let mock = Mock::new();
+    let mut display = awake_display(&mock);
+    display.set_temperature(Some(25.0));
+
+    display.flush_update(&mut mock.delay()).unwrap();
+
+    assert_eq!(
+        mock.transactions().unwrap(),
+        [
+            transaction(Command::TempWrite, &[0x19, 0x00]),
+            transaction(Command::DispCtrl2, &[0xD4]),
+            transaction(Command::MasterActivate, &[]),
+        ]
+    );
+}
+
+#[test]
+fn flush_powers_up_writes_updates_and_sleeps() {
+    let mock = Mock::new();
+    let mut display = display(&mock);
+
+    display.flush(&mut mock.delay()).unwrap();
+
+    let power_up: Vec<_> = power_up_sequence()
+        .into_iter()
+        .map(|transaction| transaction.command)
+        .collect();
+    let commands = commands(&mock);
+    assert_eq!(commands[..power_up.len()], power_up);
+    assert_eq!(
+        commands[power_up.len()..],
+        [
+            Command::RamXPos,
+            Command::RamYPos,
+            Command::RamXCount,
+            Command::RamYCount,
+            Command::WriteRAM1,
+            Command::RamXCount,
+            Command::RamYCount,
+            Command::WriteRAM2,
+            Command::DispCtrl2,
+            Command::MasterActivate,
+            Command::Sleep,
+        ]
+    );
+}
+
+#[test]
+fn flush_partial_restores_full_window() {
+    let mock = Mock::new();
+    let mut display = display(&mock);
+
+    let area = Rectangle::new(Point::new(10, 10), Size::new(8, 8));
+    display.flush_partial(area, &mut mock.delay()).unwrap();
+
+    let transactions = mock.transactions().unwrap();
+    assert_eq!(
+        transactions[transactions.len() - 3..],
+        [
+            transaction(Command::RamXPos, &[0x01, 0x10]),
+            transaction(Command::RamYPos, &[249, 0, 0, 0]),
+            transaction(Command::Sleep, &[0x01]),
+        ]
+    );
+}
+
+#[test]
+fn power_down_enters_deep_sleep() {
+    let mock = Mock::new();
+    let mut display = display(&mock);
+
+    display.power_down().unwrap();
+    display.power_down().unwrap();
+
+    assert_eq!(display.power_state(), PowerState::DeepSleep);
+    assert_eq!(
+        mock.transactions().unwrap(),
+        [transaction(Command::Sleep, &[0x01])]
+    );
+}
+
+#[test]
+fn flush_display_powers_up_reset_controller() {
+    let mock = Mock::new();
+    let mut display = display(&mock);
+    assert_eq!(display.power_state(), PowerState::Reset);
+
+    display.flush_display(&mut mock.delay()).unwrap();
+
+    assert_eq!(display.power_state(), PowerState::Awake);
+    assert_eq!(mock.events()[0], Event::Reset(false));
+    let transactions = mock.transactions().unwrap();
+    assert_eq!(
+        transactions[..power_up_sequence().len()],
+        power_up_sequence()
+    );
+}
+
+#[test]
+fn power_down_without_reset_pin_stays_awake() {
+    let mock = Mock::new();
+    let mut display = Ssd1680::new(
+        mock.spi(),
+        mock.dc(),
+        None::<NoPin>,
+        Some(mock.busy()),
+        DisplaySize250x122,
+        DisplayRotation::Rotate0,
+    );
+
+    display.power_down().unwrap();
+
+    assert!(mock.events().is_empty());
+}
+
+/// Reset pin whose driver reports its own error type
+struct BrokenReset;
+
+#[derive(Debug, PartialEq)]
+struct ResetFault;
+
+impl OutputPin for BrokenReset {
+    type Error = ResetFault;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Err(ResetFault)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Err(ResetFault)
+    }
+}
+
+#[test]
+fn pin_errors_keep_their_own_type() {
+    let mock = Mock::new();
+    let mut display = Ssd1680::new(
+        mock.spi(),
+        mock.dc(),
+        Some(BrokenReset),
+        Some(mock.busy()),
+        DisplaySize250x122,
+        DisplayRotation::Rotate0,
+    );
+
+    let result = display.power_up(&mut mock.delay());
+
+    assert!(matches!(
+        result,
+        Err(Error::Pin(PinError::Reset(ResetFault)))
+    ));
+}
+
+#[test]
+fn read_temperature_loads_and_reads_the_sensor() {
+    let mock = Mock::new();
+    let mut display = awake_display(&mock);
+
+    mock.queue_read(&[0xE6, 0x80]);
+    let celsius = display.read_temperature(&mut mock.delay()).unwrap();
+
+    assert_eq!(celsius, -25.5);
+    assert_eq!(
+        mock.transactions().unwrap(),
+        [
+            transaction(Command::TempSensor, &[0x80]),
+            transaction(Command::DispCtrl2, &[0xB1]),
+            transaction(Command::MasterActivate, &[]),
+            transaction(Command::TempRead, &[]),
+            transaction(Command::TempSensor, &[0x80]),
+        ]
+    );
+    assert!(mock.events().contains(&Event::Read(vec![0xE6, 0x80])));
+}
+
+#[test]
+fn read_temperature_leaves_power_state_as_it_was() {
+    let mock = Mock::new();
+    let mut display = awake_display(&mock);
+
+    mock.queue_read(&[0x19, 0x00]);
+    display.read_temperature(&mut mock.delay()).unwrap();
+
+    assert_eq!(display.power_state(), PowerState::Awake);
+    assert!(!mock.events().contains(&Event::Reset(false)));
+
+    display.power_down().unwrap();
+    mock.clear();
+    mock.queue_read(&[0x19, 0x00]);
+    display.read_temperature(&mut mock.delay()).unwrap();
+
+    assert_eq!(display.power_state(), PowerState::DeepSleep);
+    assert_eq!(mock.events()[0], Event::Reset(false));
+}
+
+#[test]
+fn read_temperature_keeps_custom_waveform() {
+    let mock = Mock::new();
+    let mut display = display(&mock);
+    display.set_waveform(Some(Waveform::from_bytes(&[0x11; LUT_SIZE + 6])));
+    display.power_up(&mut mock.delay()).unwrap();
+
+    mock.queue_read(&[0x19, 0x00]);
+    display.read_temperature(&mut mock.delay()).unwrap();
+    mock.clear();
+    display.flush_update(&mut mock.delay()).unwrap();
+
+    // loading the sensor also loaded the OTP waveform over the custom one
+    let transactions = mock.transactions().unwrap();
+    let lut = transactions
+        .iter()
+        .position(|sent| sent.command == Command::WriteLut)
+        .expect("waveform uploaded again");
+    let update = transactions
+        .iter()
+        .position(|sent| *sent == transaction(Command::DispCtrl2, &[0xC4]))
+        .unwrap();
+    assert!(lut < update);
+}
+
+#[test]
+fn busy_wait_times_out() {
+    let mock = Mock::new();
+    let mut display = awake_display(&mock);
+    display.set_busy_timeout(5);
+    mock.set_busy_polls(100);
+
+    let result = display.flush_update(&mut mock.delay());
+
+    assert!(matches!(result, Err(Error::BusyTimeout)));
+    assert_eq!(
+        mock.events()
+            .iter()
+            .filter(|event| **event == Event::Delay(1))
+            .count(),
+        5
+    );
+}
+
+#[test]
+fn begin_update_returns_before_refresh() {
+    let mock = Mock::new();
+    let mut display = awake_display(&mock);
+    mock.set_busy_polls(3);
+
+    display.begin_update(&mut mock.delay()).unwrap();
+
+    assert!(display.is_updating());
+    assert!(!mock.events().contains(&Event::Delay(1)));
+    assert_eq!(
+        mock.transactions().unwrap(),
+        [
+            transaction(Command::DispCtrl2, &[0xF4]),
+            transaction(Command::MasterActivate, &[]),
+        ]
+    );
+    assert!(matches!(display.power_down(), Err(Error::Busy)));
+
+    mock.clear();
+    display.wait_update_complete(&mut mock.delay()).unwrap();
+
+    assert!(!display.is_updating());
+    assert_eq!(mock.events(), vec![Event::Delay(1); 3]);
+    display.power_down().unwrap();
+}
+
+#[test]
+fn flush_display_waits_for_running_update() {
+    let mock = Mock::new();
+    let mut display = awake_display(&mock);
+
+    display.begin_update(&mut mock.delay()).unwrap();
+    mock.set_busy_polls(2);
+    mock.clear();
+    display.flush_display(&mut mock.delay()).unwrap();
+
+    assert!(!display.is_updating());
+    assert_eq!(mock.events()[..2], vec![Event::Delay(1); 2]);
+    assert_eq!(commands(&mock)[0], Command::RamXPos);
+}